@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 
 declare_id!("F5wQsBbjHAViAimLojNZRCxdecvHnUTfWqKnLCz2Bdho");
 
@@ -7,127 +10,643 @@ declare_id!("F5wQsBbjHAViAimLojNZRCxdecvHnUTfWqKnLCz2Bdho");
 pub mod battle_memecoin_club {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, bump: u8) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, bump: u8, treasury: Pubkey) -> Result<()> {
+        // Every other instruction re-derives the house PDA from the stored bump
+        require!(bump == ctx.bumps.house_wallet, BattleError::InvalidBump);
+        
         let house_wallet = &mut ctx.accounts.house_wallet;
         house_wallet.bump = bump;
         house_wallet.authority = ctx.accounts.authority.key();
+        house_wallet.pending_authority = None;
+        house_wallet.treasury = treasury;
+        house_wallet.operators = Vec::new();
+        house_wallet.resolvers = Vec::new();
+        house_wallet.treasurers = Vec::new();
+        house_wallet.pausers = Vec::new();
         house_wallet.paused = false;
         house_wallet.initialized = true;
+        house_wallet.fee_bps = HouseWallet::DEFAULT_FEE_BPS;
+        house_wallet.max_fee = HouseWallet::DEFAULT_MAX_FEE;
+        house_wallet.min_bet = HouseWallet::DEFAULT_MIN_BET;
+        house_wallet.max_bet = HouseWallet::DEFAULT_MAX_BET;
+        house_wallet.draw_handling = DrawHandling::FullRefund;
+        house_wallet.cancel_fee_bps = 0;
+        house_wallet.hedging_policy = HedgingPolicy::SameFighterOnly;
         
         msg!("House wallet initialized with authority: {:?}", house_wallet.authority);
         Ok(())
     }
 
-    pub fn create_match_account(ctx: Context<CreateMatchAccount>, match_id: String, fighter1: String, fighter2: String) -> Result<()> {
-        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+    pub fn migrate_house_wallet(ctx: Context<MigrateHouseWallet>, treasury: Pubkey) -> Result<()> {
+        let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
+        let old_len = house_wallet_info.data_len();
+        let new_len = 8 + HouseWallet::SPACE;
+        require!(old_len < new_len, BattleError::AlreadyMigrated);
+        
+        let legacy = {
+            let data = house_wallet_info.try_borrow_data()?;
+            require!(data.starts_with(HouseWallet::DISCRIMINATOR), BattleError::NotLegacyAccount);
+            LegacyHouseWallet::deserialize(&mut &data[8..])?
+        };
+        require!(legacy.authority == ctx.accounts.authority.key(), BattleError::Unauthorized);
+        
+        // The authority pays the extra rent, so stakes held by the house wallet are left untouched
+        let rent = Rent::get()?;
+        let rent_top_up = rent.minimum_balance(new_len).saturating_sub(rent.minimum_balance(old_len));
+        if rent_top_up > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    ctx.accounts.authority.key,
+                    house_wallet_info.key,
+                    rent_top_up,
+                ),
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    house_wallet_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        house_wallet_info.realloc(new_len, true)?;
+        
+        // The legacy bump was caller-supplied, so store the canonical one every instruction re-derives
+        let house_wallet = HouseWallet {
+            authority: legacy.authority,
+            pending_authority: None,
+            bump: ctx.bumps.house_wallet,
+            paused: legacy.paused,
+            initialized: legacy.initialized,
+            fee_bps: HouseWallet::DEFAULT_FEE_BPS,
+            max_fee: HouseWallet::DEFAULT_MAX_FEE,
+            min_bet: HouseWallet::DEFAULT_MIN_BET,
+            max_bet: HouseWallet::DEFAULT_MAX_BET,
+            treasury,
+            operators: Vec::new(),
+            resolvers: Vec::new(),
+            treasurers: Vec::new(),
+            pausers: Vec::new(),
+            draw_handling: DrawHandling::FullRefund,
+            cancel_fee_bps: 0,
+            hedging_policy: HedgingPolicy::SameFighterOnly,
+        };
+        house_wallet.try_serialize(&mut &mut house_wallet_info.try_borrow_mut_data()?[..])?;
+        
+        emit!(HouseWalletMigrated {
+            authority: house_wallet.authority,
+            treasury,
+            rent_top_up,
+        });
+        
+        msg!("House wallet migrated to the current layout with treasury: {}", treasury);
+        Ok(())
+    }
+
+    pub fn settle_legacy_match<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleLegacyMatch<'info>>,
+        match_id: String,
+    ) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        
+        let match_info = ctx.accounts.match_account.to_account_info();
+        let mut legacy = {
+            let data = match_info.try_borrow_data()?;
+            require!(
+                data.len() == 8 + LegacyMatchAccount::SPACE && data.starts_with(MatchAccount::DISCRIMINATOR),
+                BattleError::NotLegacyAccount
+            );
+            LegacyMatchAccount::deserialize(&mut &data[8..])?
+        };
+        require!(legacy.match_id == match_id, BattleError::InvalidMatchId);
+        
+        // Legacy matches can no longer be ended, so anything short of Completed is refunded
+        let winner = match legacy.status {
+            MatchStatus::Completed => legacy.winner.clone(),
+            _ => None,
+        };
+        let total_winning_bets = match &winner {
+            Some(winner) if *winner == legacy.fighter1 => legacy.total_bets_fighter1,
+            Some(_) => legacy.total_bets_fighter2,
+            None => 0,
+        };
+        
+        // Legacy stakes were held by the house wallet itself
+        let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(house_wallet_info.data_len());
+        let mut total_paid: u64 = 0;
+        let mut settled_count: u32 = 0;
+        
+        for bet in legacy.bets.iter_mut().filter(|bet| !bet.claimed) {
+            let payout = match &winner {
+                Some(winner) if bet.fighter == *winner => bet.amount
+                    .checked_add(calculate_prize_share(bet.amount, legacy.prize_pool, total_winning_bets)?)
+                    .ok_or(BattleError::Overflow)?,
+                Some(_) => 0,
+                None => bet.amount,
+            };
+            
+            if payout > 0 {
+                // Bettors left out of remaining_accounts are settled by a later call
+                let Some(bettor) = ctx.remaining_accounts.iter().find(|account| account.key() == bet.bettor) else {
+                    continue;
+                };
+                let available = house_wallet_info.lamports().saturating_sub(rent_exempt_minimum);
+                if available < payout {
+                    return Err(payout_shortfall(bet.bettor, payout));
+                }
+                **house_wallet_info.try_borrow_mut_lamports()? -= payout;
+                **bettor.try_borrow_mut_lamports()? += payout;
+                total_paid = total_paid.checked_add(payout).ok_or(BattleError::Overflow)?;
+            }
+            
+            bet.claimed = true;
+            settled_count += 1;
+        }
+        
+        // Once every bet is settled the account is closed, freeing its match ID
+        let closed = legacy.bets.iter().all(|bet| bet.claimed);
+        if closed {
+            let authority_info = ctx.accounts.authority.to_account_info();
+            **authority_info.try_borrow_mut_lamports()? += match_info.lamports();
+            **match_info.try_borrow_mut_lamports()? = 0;
+            match_info.assign(&System::id());
+            match_info.realloc(0, false)?;
+        } else {
+            legacy.serialize(&mut &mut match_info.try_borrow_mut_data()?[8..])?;
+        }
+        
+        emit!(LegacyMatchSettled {
+            match_account: match_info.key(),
+            match_id: match_id.clone(),
+            settled_count,
+            total_paid,
+            closed,
+        });
+        
+        msg!(
+            "Legacy match settled - ID: {}, Bets settled: {}, Total paid: {}, Closed: {}",
+            match_id,
+            settled_count,
+            total_paid,
+            closed
+        );
+        Ok(())
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        
+        // The current authority stays in control until the proposed key accepts
+        let house_wallet = &mut ctx.accounts.house_wallet;
+        house_wallet.pending_authority = Some(new_authority);
+        emit!(AuthorityProposed {
+            authority: house_wallet.authority,
+            pending_authority: new_authority,
+        });
+        msg!("Authority transfer proposed to: {}", new_authority);
+        
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let house_wallet = &mut ctx.accounts.house_wallet;
+        require!(
+            house_wallet.pending_authority == Some(ctx.accounts.new_authority.key()),
+            BattleError::Unauthorized
+        );
+        
+        let previous_authority = house_wallet.authority;
+        house_wallet.authority = ctx.accounts.new_authority.key();
+        house_wallet.pending_authority = None;
+        emit!(AuthorityTransferred {
+            previous_authority,
+            authority: house_wallet.authority,
+        });
+        msg!("Authority transferred from {} to {}", previous_authority, house_wallet.authority);
+        
+        Ok(())
+    }
+
+    pub fn add_operator(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        add_role_member(&mut ctx.accounts.house_wallet, Role::Operator, member)
+    }
+
+    pub fn remove_operator(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        remove_role_member(&mut ctx.accounts.house_wallet, Role::Operator, member)
+    }
+
+    pub fn add_resolver(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        add_role_member(&mut ctx.accounts.house_wallet, Role::Resolver, member)
+    }
+
+    pub fn remove_resolver(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
         require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        remove_role_member(&mut ctx.accounts.house_wallet, Role::Resolver, member)
+    }
+
+    pub fn add_treasurer(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        add_role_member(&mut ctx.accounts.house_wallet, Role::Treasurer, member)
+    }
+
+    pub fn remove_treasurer(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        remove_role_member(&mut ctx.accounts.house_wallet, Role::Treasurer, member)
+    }
+
+    pub fn add_pauser(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        add_role_member(&mut ctx.accounts.house_wallet, Role::Pauser, member)
+    }
+
+    pub fn remove_pauser(ctx: Context<UpdateRole>, member: Pubkey) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        remove_role_member(&mut ctx.accounts.house_wallet, Role::Pauser, member)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16,
+        max_fee: u64,
+        min_bet: u64,
+        max_bet: u64,
+        draw_handling: DrawHandling,
+        cancel_fee_bps: u16,
+        hedging_policy: HedgingPolicy,
+    ) -> Result<()> {
+        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(fee_bps <= MAX_BPS, BattleError::InvalidFeeBps);
+        require!(cancel_fee_bps <= MAX_BPS, BattleError::InvalidFeeBps);
+        require!(min_bet > 0 && min_bet <= max_bet, BattleError::InvalidBetLimits);
+        
+        let house_wallet = &mut ctx.accounts.house_wallet;
+        house_wallet.fee_bps = fee_bps;
+        house_wallet.max_fee = max_fee;
+        house_wallet.min_bet = min_bet;
+        house_wallet.max_bet = max_bet;
+        house_wallet.draw_handling = draw_handling;
+        house_wallet.cancel_fee_bps = cancel_fee_bps;
+        house_wallet.hedging_policy = hedging_policy;
+        
+        emit!(ConfigUpdated {
+            fee_bps,
+            max_fee,
+            min_bet,
+            max_bet,
+            draw_handling,
+            cancel_fee_bps,
+            hedging_policy,
+        });
+        msg!(
+            "Config updated - Fee bps: {}, Max fee: {}, Min bet: {}, Max bet: {}, Draw handling: {:?}, Cancel fee bps: {}, Hedging: {:?}",
+            fee_bps,
+            max_fee,
+            min_bet,
+            max_bet,
+            draw_handling,
+            cancel_fee_bps,
+            hedging_policy
+        );
+        Ok(())
+    }
+
+    pub fn create_match_account(
+        ctx: Context<CreateMatchAccount>,
+        match_id: String,
+        fighters: Vec<String>,
+        schedule: MatchSchedule,
+    ) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
+
+        require!(match_id.len() <= 32, BattleError::InvalidMatchIdLength);
+        
+        validate_fighters(&fighters)?;
+        
+        let match_account = &mut ctx.accounts.match_account;
+        init_match_account(match_account, &ctx.accounts.house_wallet, &match_id, &fighters, &schedule)?;
+        match_account.bump = ctx.bumps.match_account;
+        emit_match_created(match_account);
+        
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.match_account = match_account.key();
+        escrow.bump = ctx.bumps.escrow;
+        
+        msg!("Match account created with ID: {}, Fighters: {}", match_id, fighters.join(", "));
+        Ok(())
+    }
+
+    pub fn create_token_match_account(
+        ctx: Context<CreateTokenMatchAccount>,
+        match_id: String,
+        fighters: Vec<String>,
+        min_bet: u64,
+        max_bet: u64,
+        max_fee: u64,
+        schedule: MatchSchedule,
+    ) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
+
+        require!(match_id.len() <= 32, BattleError::InvalidMatchIdLength);
+        require!(min_bet > 0 && min_bet <= max_bet, BattleError::InvalidBetLimits);
+        
+        validate_fighters(&fighters)?;
+        
+        let match_account = &mut ctx.accounts.match_account;
+        init_match_account(match_account, &ctx.accounts.house_wallet, &match_id, &fighters, &schedule)?;
+        // The house limits are in lamports, so token matches carry their own in mint units
+        match_account.min_bet = min_bet;
+        match_account.max_bet = max_bet;
+        match_account.max_fee = max_fee;
+        match_account.bet_mint = Some(ctx.accounts.bet_mint.key());
+        match_account.vault_bump = ctx.bumps.vault;
+        match_account.bump = ctx.bumps.match_account;
+        emit_match_created(match_account);
+        
+        msg!(
+            "Token match account created with ID: {}, Fighters: {}, Mint: {}",
+            match_id,
+            fighters.join(", "),
+            ctx.accounts.bet_mint.key()
+        );
+        Ok(())
+    }
+
+    pub fn create_tournament(
+        ctx: Context<CreateTournament>,
+        tournament_id: String,
+        pool_match_id: String,
+        fighters: Vec<String>,
+        pool_schedule: MatchSchedule,
+    ) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
+
+        require!(tournament_id.len() <= 32, BattleError::InvalidMatchIdLength);
+        require!(pool_match_id.len() <= 32, BattleError::InvalidMatchIdLength);
+        require!(
+            fighters.len() >= 2 && fighters.len().is_power_of_two(),
+            BattleError::InvalidBracketSize
+        );
+        validate_fighters(&fighters)?;
+        
+        // Outright winner bets go into a battle royale over every entrant
+        let winner_pool = &mut ctx.accounts.winner_pool;
+        init_match_account(winner_pool, &ctx.accounts.house_wallet, &pool_match_id, &fighters, &pool_schedule)?;
+        winner_pool.tournament = Some(ctx.accounts.tournament.key());
+        winner_pool.bump = ctx.bumps.winner_pool;
+        emit_match_created(winner_pool);
+        
+        let winner_pool_escrow = &mut ctx.accounts.winner_pool_escrow;
+        winner_pool_escrow.match_account = winner_pool.key();
+        winner_pool_escrow.bump = ctx.bumps.winner_pool_escrow;
+        
+        let tournament = &mut ctx.accounts.tournament;
+        tournament.tournament_id = tournament_id.clone();
+        tournament.matches = vec![Pubkey::default(); fighters.len() - 1];
+        tournament.fighters = fighters;
+        tournament.winner_pool = winner_pool.key();
+        tournament.champion = None;
+        tournament.bump = ctx.bumps.tournament;
+        
+        msg!(
+            "Tournament created with ID: {}, Fighters: {}, Winner pool: {}",
+            tournament_id,
+            tournament.fighters.join(", "),
+            pool_match_id
+        );
+        Ok(())
+    }
+
+    pub fn create_tournament_match(
+        ctx: Context<CreateTournamentMatch>,
+        tournament_id: String,
+        slot: u8,
+        match_id: String,
+        schedule: MatchSchedule,
+    ) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
 
         require!(match_id.len() <= 32, BattleError::InvalidMatchIdLength);
-        require!(fighter1.len() <= 10, BattleError::InvalidFighterLength);
-        require!(fighter2.len() <= 10, BattleError::InvalidFighterLength);
         
+        let tournament = &mut ctx.accounts.tournament;
+        let slot_index = slot as usize;
+        require!(slot_index < tournament.matches.len(), BattleError::InvalidBracketSlot);
+        require!(tournament.matches[slot_index] == Pubkey::default(), BattleError::BracketSlotTaken);
+        
+        // Later rounds start with undecided fighters that feeder matches fill in
+        let fighters = tournament.slot_fighters(slot_index);
         let match_account = &mut ctx.accounts.match_account;
-        match_account.match_id = match_id.clone();
-        match_account.fighter1 = fighter1.clone();
-        match_account.fighter2 = fighter2.clone();
-        match_account.total_bets_fighter1 = 0;
-        match_account.total_bets_fighter2 = 0;
-        match_account.status = MatchStatus::Preparation;
-        match_account.winner = None;
-        match_account.prize_pool = 0;
-        match_account.bets = Vec::new();
+        init_match_account(match_account, &ctx.accounts.house_wallet, &match_id, &fighters, &schedule)?;
+        match_account.tournament = Some(tournament.key());
+        match_account.bracket_slot = Some(slot);
         match_account.bump = ctx.bumps.match_account;
+        tournament.matches[slot_index] = match_account.key();
+        emit_match_created(match_account);
+        
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.match_account = match_account.key();
+        escrow.bump = ctx.bumps.escrow;
         
-        msg!("Match account created with ID: {}, Fighter1: {}, Fighter2: {}", match_id, fighter1, fighter2);
+        msg!(
+            "Tournament match created - Tournament: {}, Slot: {}, Match ID: {}",
+            tournament_id,
+            slot,
+            match_id
+        );
         Ok(())
     }
 
     pub fn place_bet(ctx: Context<PlaceBet>, match_id: String, fighter: String, amount: u64) -> Result<()> {
         require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
-        require!(amount >= 50_000_000, BattleError::BetTooSmall); // 0.05 SOL minimum
+        
+        // Matches created while a zero minimum was allowed still must not take empty bets
+        require!(amount > 0, BattleError::BetTooSmall);
         
         let match_account = &mut ctx.accounts.match_account;
+        require!(amount >= match_account.min_bet, BattleError::BetTooSmall);
+        require!(amount <= match_account.max_bet, BattleError::BetTooLarge);
         
         // Validate match_id and fighter length
         require!(match_id.len() <= 32, BattleError::InvalidMatchIdLength);
         require!(fighter.len() <= 10, BattleError::InvalidFighterLength);
         
-        // For a brand new match, just set the match_id and status
-        if match_account.total_bets_fighter1 == 0 && match_account.total_bets_fighter2 == 0 {
-            match_account.match_id = match_id.clone();
-            match_account.status = MatchStatus::Preparation;
-            match_account.winner = None;
-        }
-
         require!(match_account.status == MatchStatus::Preparation, BattleError::MatchNotInPreparation);
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
         
-        // Check if user has already bet in this match
-        require!(
-            !match_account.bets.iter().any(|bet| bet.bettor == ctx.accounts.bettor.key()),
-            BattleError::AlreadyBet
-        );
-
-        // Check if bets vector has reached maximum size
-        require!(match_account.bets.len() < 100, BattleError::TooManyBets);
+        // Bracket matches open once both feeder winners are known
+        require!(match_account.fighters_decided(), BattleError::FightersNotDecided);
+        
+        // Bets are only accepted inside the scheduled betting window
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= match_account.betting_opens_at, BattleError::BettingNotOpen);
+        require!(now < match_account.betting_closes_at, BattleError::BettingClosed);
+        
+        // Repeat bets top up the wallet's position; other fighters go in a hedge bet
+        let main_bet = &ctx.accounts.bet;
+        let hedging = main_bet.amount > 0 && main_bet.fighter != fighter;
+        if hedging {
+            require!(match_account.hedging_policy == HedgingPolicy::AllowHedging, BattleError::HedgingNotAllowed);
+        }
+        require!(hedging == ctx.accounts.hedge_bet.is_some(), BattleError::InvalidHedgeBet);
+        
+        // A refunded position is settled; new money on it could never be paid out
+        require!(!main_bet.claimed, BattleError::AlreadyClaimed);
+        if let Some(hedge_bet) = ctx.accounts.hedge_bet.as_ref() {
+            require!(!hedge_bet.claimed, BattleError::AlreadyClaimed);
+        }
+        
+        // Exposure caps: a wallet's stake across its bets, and each fighter's pool
+        let wallet_total = main_bet.wallet_total.checked_add(amount).ok_or(BattleError::Overflow)?;
+        require!(wallet_total <= match_account.max_wallet_total, BattleError::WalletLimitExceeded);
+        let fighter_pool = match_account.fighter(&fighter).ok_or(BattleError::InvalidFighter)?
+            .total_bets
+            .checked_add(amount)
+            .ok_or(BattleError::Overflow)?;
+        require!(fighter_pool <= match_account.max_fighter_pool, BattleError::FighterPoolLimitExceeded);
 
-        // Transfer SOL from bettor to house wallet
-        invoke(
-            &system_instruction::transfer(
-                ctx.accounts.bettor.key,
-                ctx.accounts.house_wallet.to_account_info().key,
+        let amount = if match_account.bet_mint.is_some() {
+            // Transfer tokens from bettor to the match vault
+            let (bet_mint, vault, token_program) = token_accounts(
+                &ctx.accounts.bet_mint,
+                &ctx.accounts.vault,
+                &ctx.accounts.token_program,
+            )?;
+            let bettor_token_account = ctx.accounts.bettor_token_account.as_ref()
+                .ok_or(BattleError::MissingTokenAccounts)?;
+            let vault_before = vault.amount;
+            
+            transfer_checked(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: bettor_token_account.to_account_info(),
+                        mint: bet_mint.to_account_info(),
+                        to: vault.to_account_info(),
+                        authority: ctx.accounts.bettor.to_account_info(),
+                    },
+                ),
                 amount,
-            ),
-            &[
-                ctx.accounts.bettor.to_account_info(),
-                ctx.accounts.house_wallet.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+                bet_mint.decimals,
+            )?;
+            
+            // Token-2022 transfer fees are withheld on the way in, so only credit what arrived
+            let vault = ctx.accounts.vault.as_mut().unwrap();
+            vault.reload()?;
+            let received = vault.amount.checked_sub(vault_before).ok_or(BattleError::Overflow)?;
+            require!(received > 0 && received >= match_account.min_bet, BattleError::BetTooSmall);
+            received
+        } else {
+            // Transfer SOL from bettor to the match escrow
+            let escrow = escrow_info(&ctx.accounts.escrow)?;
+            invoke(
+                &system_instruction::transfer(ctx.accounts.bettor.key, escrow.key, amount),
+                &[
+                    ctx.accounts.bettor.to_account_info(),
+                    escrow,
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+            amount
+        };
 
-        // Record the bet
-        let bet = Bet {
-            bettor: ctx.accounts.bettor.key(),
-            amount,
-            fighter: fighter.clone(),
-            claimed: false,
+        // The main bet keeps the wallet's running total and its hedge count
+        let main_bet = &mut ctx.accounts.bet;
+        main_bet.wallet_total = main_bet.wallet_total.checked_add(amount).ok_or(BattleError::Overflow)?;
+        let (bet, bump) = match ctx.accounts.hedge_bet.as_mut() {
+            Some(hedge_bet) => {
+                if hedge_bet.amount == 0 {
+                    main_bet.hedge_count += 1;
+                }
+                (hedge_bet, ctx.bumps.hedge_bet.unwrap_or_default())
+            },
+            None => (main_bet, ctx.bumps.bet),
         };
-        
-        match_account.bets.push(bet);
 
-        // Check which fighter the bet is for and update totals
-        if fighter == match_account.fighter1 || (match_account.fighter1.is_empty() && match_account.fighter2 != fighter) {
-            // If betting on fighter1 or fighter1 is empty and not betting on fighter2
-            if match_account.fighter1.is_empty() {
-                match_account.fighter1 = fighter.clone();
-            }
-            match_account.total_bets_fighter1 += amount;
-        } else if fighter == match_account.fighter2 || (match_account.fighter2.is_empty() && match_account.fighter1 != fighter) {
-            // If betting on fighter2 or fighter2 is empty and not betting on fighter1
-            if match_account.fighter2.is_empty() {
-                match_account.fighter2 = fighter.clone();
+        // Check which fighter the bet is for and update its pool
+        let pool = match_account.fighter_mut(&fighter).ok_or(BattleError::InvalidFighter)?;
+        pool.total_bets = pool.total_bets.checked_add(amount).ok_or(BattleError::Overflow)?;
+        if bet.amount == 0 {
+            pool.bet_count += 1;
+        }
+        let fighter_pool = pool.total_bets;
+
+        // Record the bet, or add to it; a cancelled bet keeps the index it was first given
+        if bet.amount == 0 {
+            if bet.match_account == Pubkey::default() {
+                bet.index = match_account.next_bet_index;
+                match_account.next_bet_index = match_account.next_bet_index
+                    .checked_add(1)
+                    .ok_or(BattleError::Overflow)?;
             }
-            match_account.total_bets_fighter2 += amount;
-        } else {
-            // Invalid fighter
-            return err!(BattleError::InvalidFighter);
+            bet.match_account = match_account.key();
+            bet.bettor = ctx.accounts.bettor.key();
+            bet.fighter = fighter.clone();
+            bet.claimed = false;
+            bet.bump = bump;
+            bet.hedge = hedging;
         }
+        bet.amount = bet.amount.checked_add(amount).ok_or(BattleError::Overflow)?;
+        
+        emit!(BetPlaced {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            bettor: bet.bettor,
+            fighter: fighter.clone(),
+            amount,
+            bet_total: bet.amount,
+            fighter_pool,
+            total_pool: match_account.total_pool(),
+        });
 
         msg!(
-            "Bet placed - Match: {}, Fighter: {}, Amount: {}, Total F1: {}, Total F2: {}",
+            "Bet placed - Match: {}, Fighter: {}, Amount: {}, Fighter Pool: {}, Total Pool: {}",
             match_id,
             fighter,
             amount,
-            match_account.total_bets_fighter1,
-            match_account.total_bets_fighter2
+            fighter_pool,
+            match_account.total_pool()
         );
 
         Ok(())
     }
 
+    pub fn cancel_bet(ctx: Context<WithdrawBet>, match_id: String) -> Result<()> {
+        // The main bet carries the wallet's totals, so it goes last
+        require!(ctx.accounts.bet.hedge_count == 0, BattleError::HedgeBetsOpen);
+        
+        let amount = ctx.accounts.bet.amount;
+        require!(amount > 0, BattleError::BetCancelled);
+        withdraw_stake(ctx.accounts, &match_id, amount)?;
+        
+        if let Some(main_bet) = ctx.accounts.main_bet.as_mut() {
+            main_bet.hedge_count -= 1;
+        }
+        
+        // The emptied bet stays open as a marker at its index so payout pages can step over it;
+        // its rent comes back through close_bet_account, and betting again reuses it
+        let pool = ctx.accounts.match_account.fighter_mut(&ctx.accounts.bet.fighter)
+            .ok_or(BattleError::InvalidFighter)?;
+        pool.bet_count -= 1;
+        Ok(())
+    }
+
+    pub fn decrease_bet(ctx: Context<WithdrawBet>, match_id: String, amount: u64) -> Result<()> {
+        let bet = &ctx.accounts.bet;
+        require!(amount > 0 && amount < bet.amount, BattleError::InvalidDecreaseAmount);
+        require!(bet.amount - amount >= ctx.accounts.match_account.min_bet, BattleError::BetTooSmall);
+        
+        withdraw_stake(ctx.accounts, &match_id, amount)
+    }
+
     pub fn update_match_status(ctx: Context<UpdateMatchStatus>, status: String, match_id: String) -> Result<()> {
         require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
         
         let match_account = &mut ctx.accounts.match_account;
         
@@ -137,7 +656,7 @@ pub mod battle_memecoin_club {
         match status.as_str() {
             "Battle" => {
                 require!(match_account.status == MatchStatus::Preparation, BattleError::InvalidStatusTransition);
-                match_account.status = MatchStatus::Battle;
+                set_status(match_account, MatchStatus::Battle);
             },
             _ => return err!(BattleError::InvalidStatus)
         }
@@ -146,219 +665,240 @@ pub mod battle_memecoin_club {
         Ok(())
     }
 
-    pub fn end_match(ctx: Context<EndMatch>, match_id: String, winner: String) -> Result<()> {
+    pub fn set_payout_split(ctx: Context<SetPayoutSplit>, match_id: String, payout_split: Vec<u16>) -> Result<()> {
         require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
         
         let match_account = &mut ctx.accounts.match_account;
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
-        require!(match_account.status == MatchStatus::Battle, BattleError::MatchNotInBattle);
-        require!(match_account.winner.is_none(), BattleError::MatchAlreadyEnded);
         
-        // Validate winner is one of the fighters
+        // Bracket matches and winner pools are always settled with a single winner
+        require!(match_account.tournament.is_none(), BattleError::TournamentPayoutFixed);
+        
+        // Bettors must see the podium terms before they wager
+        require!(match_account.status == MatchStatus::Preparation, BattleError::MatchNotInPreparation);
+        require!(match_account.total_bet_count() == 0, BattleError::PayoutSplitLocked);
+        
+        // At least one fighter has to miss the podium to fund it
         require!(
-            winner == match_account.fighter1 || winner == match_account.fighter2,
-            BattleError::InvalidWinner
+            !payout_split.is_empty() &&
+            payout_split.len() <= MatchAccount::MAX_PLACES &&
+            payout_split.len() < match_account.fighters.len(),
+            BattleError::InvalidPayoutSplit
         );
-
-        // If no bets on one side, refund all bets
-        if match_account.total_bets_fighter1 == 0 || match_account.total_bets_fighter2 == 0 {
-            match_account.status = MatchStatus::Refund;
-            msg!("Match ended in refund due to no bets on one side");
-            return Ok(());
-        }
-
-        let total_losing_bets = if winner == match_account.fighter1 {
-            match_account.total_bets_fighter2
-        } else {
-            match_account.total_bets_fighter1
-        };
-
-        // Calculate fee (5% of losing bets) ensuring proper precision
-        let fee = (total_losing_bets as u128 * 5 / 100) as u64;
-        msg!("Debug - Total losing bets: {}, Fee calculated: {}", total_losing_bets, fee);
+        require!(payout_split.iter().all(|bps| *bps > 0), BattleError::InvalidPayoutSplit);
+        let total_bps: u32 = payout_split.iter().map(|bps| *bps as u32).sum();
+        require!(total_bps == MAX_BPS as u32, BattleError::InvalidPayoutSplit);
         
-        // Transfer fee to treasury
-        **ctx.accounts.house_wallet.to_account_info().try_borrow_mut_lamports()? -= fee;
-        **ctx.accounts.treasury.try_borrow_mut_lamports()? += fee;
-
-        match_account.winner = Some(winner.clone());
-        match_account.prize_pool = total_losing_bets - fee;
-        match_account.status = MatchStatus::Completed;
+        match_account.payout_split = payout_split;
+        
+        emit!(PayoutSplitChanged {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            payout_split: match_account.payout_split.clone(),
+        });
+        msg!("Payout split updated - Match: {}, Split (bps): {:?}", match_id, match_account.payout_split);
+        Ok(())
+    }
 
+    pub fn set_bet_limits(
+        ctx: Context<SetBetLimits>,
+        match_id: String,
+        min_bet: u64,
+        max_bet: u64,
+        max_wallet_total: u64,
+        max_fighter_pool: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
+        
+        let match_account = &mut ctx.accounts.match_account;
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        require!(match_account.status == MatchStatus::Preparation, BattleError::MatchNotInPreparation);
+        require!(min_bet > 0 && min_bet <= max_bet, BattleError::InvalidBetLimits);
+        require!(max_bet <= max_wallet_total, BattleError::InvalidBetLimits);
+        
+        // Only later bets are checked, so lowering a cap never unwinds existing stakes
+        match_account.min_bet = min_bet;
+        match_account.max_bet = max_bet;
+        match_account.max_wallet_total = max_wallet_total;
+        match_account.max_fighter_pool = max_fighter_pool;
+        
+        emit!(BetLimitsChanged {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            min_bet,
+            max_bet,
+            max_wallet_total,
+            max_fighter_pool,
+        });
         msg!(
-            "Match ended - ID: {}, Winner: {}, Prize Pool: {}, Fee transferred to treasury: {}",
+            "Bet limits updated - Match: {}, Min bet: {}, Max bet: {}, Max per wallet: {}, Max per fighter: {}",
             match_id,
-            winner,
-            match_account.prize_pool,
-            fee
+            min_bet,
+            max_bet,
+            max_wallet_total,
+            max_fighter_pool
         );
-
         Ok(())
     }
 
-    pub fn claim_prize(ctx: Context<ClaimPrize>, match_id: String) -> Result<()> {
+    pub fn start_battle(ctx: Context<StartBattle>, match_id: String) -> Result<()> {
         require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
         
         let match_account = &mut ctx.accounts.match_account;
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
-        require!(match_account.status == MatchStatus::Completed, BattleError::MatchNotCompleted);
+        require!(match_account.status == MatchStatus::Preparation, BattleError::InvalidStatusTransition);
         
-        // Get all necessary values before mutable borrow
-        let winner = match_account.winner.as_ref().unwrap().clone();
-        let total_winning_bets = if winner == match_account.fighter1 {
-            match_account.total_bets_fighter1
-        } else {
-            match_account.total_bets_fighter2
+        // Anyone may lock betting once the close time has passed
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= match_account.betting_closes_at, BattleError::BettingStillOpen);
+        set_status(match_account, MatchStatus::Battle);
+        
+        msg!("Betting closed - Match: {}, status updated to: Battle", match_id);
+        Ok(())
+    }
+
+    pub fn expire_match(ctx: Context<ExpireMatch>, match_id: String) -> Result<()> {
+        let match_account = &mut ctx.accounts.match_account;
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        require!(
+            match_account.status == MatchStatus::Preparation || match_account.status == MatchStatus::Battle,
+            BattleError::MatchNotFinalized
+        );
+        
+        // Anyone may refund a match the resolver failed to end in time, even while paused
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= match_account.resolution_deadline, BattleError::ResolutionDeadlineNotReached);
+        set_status(match_account, MatchStatus::Refund);
+        
+        msg!("Match expired - ID: {}, status updated to: Refund", match_id);
+        Ok(())
+    }
+
+    pub fn end_match(ctx: Context<EndMatch>, match_id: String, result: MatchResult) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Resolver), BattleError::Unauthorized);
+        
+        // Tournament winner pools are settled from the bracket, not by the resolver
+        let match_account = &ctx.accounts.match_account;
+        require!(
+            match_account.tournament.is_none() || match_account.bracket_slot.is_some(),
+            BattleError::TournamentPoolSettlesItself
+        );
+        
+        let advancing = match &result {
+            MatchResult::Winner(winner) => Some(winner.clone()),
+            MatchResult::Placements(placed) => placed.first().cloned(),
+            MatchResult::Draw | MatchResult::NoContest => None,
         };
-        let prize_pool = match_account.prize_pool;
-        
-        // Process all unclaimed winning bets
-        let remaining_accounts = ctx.remaining_accounts.iter().collect::<Vec<_>>();
-        let mut total_claimed = 0;
-        let mut claimed_count = 0;
-        
-        for bet in match_account.bets.iter_mut() {
-            if !bet.claimed && bet.fighter == winner {
-                // Find the bettor account in remaining accounts
-                let mut bettor_account_opt = None;
-                for account in remaining_accounts.iter() {
-                    if account.key() == bet.bettor {
-                        bettor_account_opt = Some(*account);
-                        break;
-                    }
-                }
-                
-                let bettor_account = bettor_account_opt
-                    .ok_or(error!(BattleError::InvalidRemainingAccounts))?;
-                
-                // Calculate prize share with overflow protection
-                let prize_share = calculate_prize_share(bet.amount, prize_pool, total_winning_bets)?;
-                let total_payout = bet.amount.checked_add(prize_share)
-                    .ok_or(BattleError::Overflow)?;
-                
-                // Transfer prize directly to bettor
-                let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
-                let transfer_result = house_wallet_info.try_borrow_mut_lamports();
-                if let Err(err) = transfer_result {
-                    msg!("Failed to borrow house wallet lamports: {}", err);
-                    continue;
-                }
-                
-                let receiver_result = bettor_account.try_borrow_mut_lamports();
-                if let Err(err) = receiver_result {
-                    msg!("Failed to borrow bettor account lamports: {}", err);
-                    continue;
-                }
-                
-                let house_lamports = &mut **transfer_result.unwrap();
-                if *house_lamports < total_payout {
-                    msg!(
-                        "Insufficient funds in house wallet to pay prize to bettor: {}, Amount: {}",
-                        bet.bettor,
-                        total_payout
-                    );
-                    continue;
-                }
-                
-                *house_lamports -= total_payout;
-                **receiver_result.unwrap() += total_payout;
-                
-                // Mark as claimed only if transfer was successful
-                bet.claimed = true;
-                total_claimed += total_payout;
-                claimed_count += 1;
-                
-                msg!(
-                    "Prize sent - Bettor: {}, Amount: {}, Prize Share: {}",
-                    bet.bettor,
-                    bet.amount,
-                    prize_share
-                );
-            }
-        }
+        resolve_match(ctx.accounts, &match_id, result)?;
         
-        if total_claimed > 0 {
-            msg!(
-                "Total prizes distributed - Match: {}, Count: {}, Total Amount: {}",
-                match_id,
-                claimed_count,
-                total_claimed
-            );
-        } else {
-            msg!("No unclaimed prizes found");
+        // The winner of a bracket match advances, even if its bets were refunded
+        if let Some(slot) = ctx.accounts.match_account.bracket_slot {
+            let winner = advancing.ok_or(BattleError::TournamentMatchNeedsWinner)?;
+            let tournament = ctx.accounts.tournament.as_mut().ok_or(BattleError::MissingTournamentAccounts)?;
+            advance_bracket(tournament, ctx.accounts.next_match.as_mut(), slot as usize, winner)?;
         }
-
+        
         Ok(())
     }
 
-    pub fn claim_refund(ctx: Context<ClaimRefund>, match_id: String) -> Result<()> {
+    pub fn advance_refunded_match(ctx: Context<AdvanceBracket>, match_id: String, winner: String) -> Result<()> {
         require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Resolver), BattleError::Unauthorized);
         
-        let match_account = &mut ctx.accounts.match_account;
+        // Expired or emergency-refunded bracket matches never ran end_match, so the resolver
+        // still names who goes through; advance_bracket refuses a slot that already advanced
+        let match_account = &ctx.accounts.match_account;
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
-        require!(match_account.status == MatchStatus::Refund, BattleError::NotRefundable);
-        
-        // Process all unclaimed refunds
-        let remaining_accounts = ctx.remaining_accounts.iter().collect::<Vec<_>>();
-        let mut total_refunded = 0;
-        let mut refunded_count = 0;
-        
-        for bet in match_account.bets.iter_mut() {
-            if !bet.claimed {
-                // Find the bettor account in remaining accounts
-                let mut bettor_account_opt = None;
-                for account in remaining_accounts.iter() {
-                    if account.key() == bet.bettor {
-                        bettor_account_opt = Some(*account);
-                        break;
-                    }
-                }
-                
-                let bettor_account = bettor_account_opt
-                    .ok_or(error!(BattleError::InvalidRemainingAccounts))?;
-                
-                // Transfer refund directly to bettor
-                let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
-                let transfer_result = house_wallet_info.try_borrow_mut_lamports();
-                if let Err(err) = transfer_result {
-                    msg!("Failed to borrow house wallet lamports: {}", err);
-                    continue;
-                }
-                
-                let receiver_result = bettor_account.try_borrow_mut_lamports();
-                if let Err(err) = receiver_result {
-                    msg!("Failed to borrow bettor account lamports: {}", err);
-                    continue;
-                }
-                
-                let house_lamports = &mut **transfer_result.unwrap();
-                if *house_lamports < bet.amount {
-                    msg!(
-                        "Insufficient funds in house wallet to refund bettor: {}, Amount: {}",
-                        bet.bettor,
-                        bet.amount
-                    );
-                    continue;
+        let slot = match_account.bracket_slot.ok_or(BattleError::NotBracketMatch)?;
+        require!(match_account.is_refundable(), BattleError::NotRefundable);
+        require!(!winner.is_empty() && match_account.fighter(&winner).is_some(), BattleError::InvalidWinner);
+        
+        advance_bracket(&mut ctx.accounts.tournament, ctx.accounts.next_match.as_mut(), slot as usize, winner)
+    }
+
+    pub fn settle_tournament_pool(ctx: Context<EndMatch>, match_id: String) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        
+        // Anyone may settle the outright winner pool once the final has a winner
+        let tournament = ctx.accounts.tournament.as_ref().ok_or(BattleError::MissingTournamentAccounts)?;
+        require_keys_eq!(tournament.winner_pool, ctx.accounts.match_account.key(), BattleError::InvalidTournament);
+        let champion = tournament.champion.clone().ok_or(BattleError::TournamentNotFinished)?;
+        
+        // Betting on the pool is over once the tournament is decided
+        let match_account = &mut ctx.accounts.match_account;
+        if match_account.status == MatchStatus::Preparation {
+            set_status(match_account, MatchStatus::Battle);
+        }
+        
+        resolve_match(ctx.accounts, &match_id, MatchResult::Winner(champion))
+    }
+
+    pub fn claim_prize<'info>(ctx: Context<'_, '_, '_, 'info, ClaimPrize<'info>>, match_id: String, strict: bool) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
+        
+        let match_account = &mut ctx.accounts.match_account;
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        require!(match_account.status == MatchStatus::Completed, BattleError::MatchNotCompleted);
+        
+        // Winning bets get their stake back plus their placement's share of the prize pool
+        let token = match match_account.bet_mint {
+            Some(_) => Some(token_accounts(&ctx.accounts.bet_mint, &ctx.accounts.vault, &ctx.accounts.token_program)?),
+            None => None,
+        };
+        let (claimed_count, total_claimed, _) = settle_bets(
+            match_account,
+            token,
+            &ctx.accounts.escrow,
+            ctx.remaining_accounts,
+            strict,
+            |match_account, bet| {
+                if match_account.placement(&bet.fighter).is_none() {
+                    return Ok(None);
                 }
-                
-                *house_lamports -= bet.amount;
-                **receiver_result.unwrap() += bet.amount;
-                
-                // Mark as claimed only if transfer was successful
-                bet.claimed = true;
-                total_refunded += bet.amount;
-                refunded_count += 1;
-                
-                msg!(
-                    "Refund sent - Bettor: {}, Amount: {}",
-                    bet.bettor,
-                    bet.amount
-                );
-            }
+                let prize_share = match_account.prize_share(bet.amount, &bet.fighter)?;
+                Ok(Some(bet.amount.checked_add(prize_share).ok_or(BattleError::Overflow)?))
+            },
+        )?;
+        
+        if total_claimed > 0 {
+            msg!(
+                "Total prizes distributed - Match: {}, Count: {}, Total Amount: {}",
+                match_id,
+                claimed_count,
+                total_claimed
+            );
+        } else {
+            msg!("No unclaimed prizes found");
         }
+
+        Ok(())
+    }
+
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRefund<'info>>, match_id: String, strict: bool) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
+        
+        let match_account = &mut ctx.accounts.match_account;
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        require!(match_account.is_refundable(), BattleError::NotRefundable);
+        
+        // Every bet gets its stake back, less its share of any draw fee
+        let token = match match_account.bet_mint {
+            Some(_) => Some(token_accounts(&ctx.accounts.bet_mint, &ctx.accounts.vault, &ctx.accounts.token_program)?),
+            None => None,
+        };
+        let (refunded_count, total_refunded, _) = settle_bets(
+            match_account,
+            token,
+            &ctx.accounts.escrow,
+            ctx.remaining_accounts,
+            strict,
+            |match_account, bet| match_account.refund_amount(bet.amount).map(Some),
+        )?;
         
         if total_refunded > 0 {
             msg!(
@@ -374,95 +914,70 @@ pub mod battle_memecoin_club {
         Ok(())
     }
 
-    pub fn set_pause_state(ctx: Context<SetPauseState>, paused: bool) -> Result<()> {
+    pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
         require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
         
+        let house_wallet = &mut ctx.accounts.house_wallet;
+        let previous_treasury = house_wallet.treasury;
+        house_wallet.treasury = treasury;
+        emit!(TreasuryChanged {
+            previous_treasury,
+            treasury,
+        });
+        msg!("Treasury set to: {}", treasury);
+        
+        Ok(())
+    }
+
+    pub fn set_pause_state(ctx: Context<SetPauseState>, paused: bool) -> Result<()> {
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Pauser), BattleError::Unauthorized);
+        
         let house_wallet = &mut ctx.accounts.house_wallet;
         house_wallet.paused = paused;
+        emit!(PauseChanged {
+            paused,
+            authority: ctx.accounts.authority.key(),
+        });
         msg!("Program pause state set to: {}", paused);
         
         Ok(())
     }
 
-    pub fn emergency_refund(ctx: Context<EmergencyRefund>, match_id: String) -> Result<()> {
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+    pub fn emergency_refund<'info>(ctx: Context<'_, '_, '_, 'info, EmergencyRefund<'info>>, match_id: String, strict: bool) -> Result<()> {
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Resolver), BattleError::Unauthorized);
         
         let match_account = &mut ctx.accounts.match_account;
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
-        require!(match_account.status != MatchStatus::Completed, BattleError::MatchAlreadyCompleted);
-        
-        // Process all unclaimed refunds
-        let remaining_accounts = ctx.remaining_accounts.iter().collect::<Vec<_>>();
-        let mut total_refunded = 0;
-        let mut refunded_count = 0;
-        let mut has_unclaimed_bets = false;
-        
-        for bet in match_account.bets.iter_mut() {
-            if !bet.claimed {
-                // Find the bettor account in remaining accounts
-                let mut bettor_account_opt = None;
-                for account in remaining_accounts.iter() {
-                    if account.key() == bet.bettor {
-                        bettor_account_opt = Some(*account);
-                        break;
-                    }
-                }
-                
-                let bettor_account = bettor_account_opt
-                    .ok_or(error!(BattleError::InvalidRemainingAccounts))?;
-                
-                // Transfer refund directly to bettor with error handling
-                let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
-                let transfer_result = house_wallet_info.try_borrow_mut_lamports();
-                if let Err(err) = transfer_result {
-                    msg!("Failed to borrow house wallet lamports: {}", err);
-                    has_unclaimed_bets = true;
-                    continue;
-                }
-                
-                let receiver_result = bettor_account.try_borrow_mut_lamports();
-                if let Err(err) = receiver_result {
-                    msg!("Failed to borrow bettor account lamports: {}", err);
-                    has_unclaimed_bets = true;
-                    continue;
-                }
-                
-                let house_lamports = &mut **transfer_result.unwrap();
-                if *house_lamports < bet.amount {
-                    msg!(
-                        "Insufficient funds in house wallet to refund bettor: {}, Amount: {}",
-                        bet.bettor,
-                        bet.amount
-                    );
-                    has_unclaimed_bets = true;
-                    continue;
-                }
-                
-                *house_lamports -= bet.amount;
-                **receiver_result.unwrap() += bet.amount;
-                
-                // Mark as claimed only if transfer was successful
-                bet.claimed = true;
-                total_refunded += bet.amount;
-                refunded_count += 1;
-                
-                msg!(
-                    "Emergency refund sent - Bettor: {}, Amount: {}",
-                    bet.bettor,
-                    bet.amount
-                );
-            }
+        // Draws and no-contests already have their own refund terms
+        require!(
+            matches!(match_account.status, MatchStatus::Preparation | MatchStatus::Battle | MatchStatus::Refund),
+            BattleError::MatchAlreadyCompleted
+        );
+        
+        // The first page freezes the match so no bet, withdrawal or result can touch the pools
+        if match_account.status != MatchStatus::Refund {
+            set_status(match_account, MatchStatus::Refund);
         }
         
-        // Check if all bets are claimed before setting match status to Refund
-        let all_claimed = !has_unclaimed_bets && match_account.bets.iter().all(|bet| bet.claimed);
+        // Refund status pays every stake back in full
+        let token = match match_account.bet_mint {
+            Some(_) => Some(token_accounts(&ctx.accounts.bet_mint, &ctx.accounts.vault, &ctx.accounts.token_program)?),
+            None => None,
+        };
+        let (refunded_count, total_refunded, has_unclaimed_bets) = settle_bets(
+            match_account,
+            token,
+            &ctx.accounts.escrow,
+            ctx.remaining_accounts,
+            strict,
+            |_, bet| Ok(Some(bet.amount)),
+        )?;
         
+        let all_claimed = !has_unclaimed_bets && match_account.claimed_count == match_account.total_bet_count();
         if all_claimed {
-            // Only update match status if all bets are claimed
-            match_account.status = MatchStatus::Refund;
-            msg!("All bets have been refunded. Match status updated to Refund.");
+            msg!("All bets have been refunded.");
         } else {
-            msg!("Not all bets have been refunded. Match status remains unchanged.");
+            msg!("Not all bets have been refunded yet. Remaining bets can be refunded in later pages.");
         }
         
         if total_refunded > 0 {
@@ -483,12 +998,26 @@ pub mod battle_memecoin_club {
         ctx: Context<TransferFromHouseWallet>, 
         amount: u64
     ) -> Result<()> {
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Treasurer), BattleError::Unauthorized);
+        
+        // Stakes sit in each match's escrow, so only the rent-exempt minimum stays put
+        let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(house_wallet_info.data_len());
+        let remaining = house_wallet_info.lamports()
+            .checked_sub(amount)
+            .ok_or(BattleError::InsufficientFunds)?;
+        require!(remaining >= rent_exempt_minimum, BattleError::WithdrawalExceedsFreeBalance);
         
         // Transfer SOL directly from house wallet to recipient
-        **ctx.accounts.house_wallet.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **house_wallet_info.try_borrow_mut_lamports()? -= amount;
         **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
         
+        emit!(HouseWithdrawal {
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            authority: ctx.accounts.authority.key(),
+        });
+        
         msg!(
             "House wallet transfer - To: {}, Amount: {}",
             ctx.accounts.recipient.key(),
@@ -498,133 +1027,271 @@ pub mod battle_memecoin_club {
         Ok(())
     }
 
-    pub fn reclaim_prize(ctx: Context<ClaimPrize>, match_id: String, bettor_pubkey: Pubkey) -> Result<()> {
+    pub fn reclaim_prize(ctx: Context<ReclaimPrize>, match_id: String, bettor_pubkey: Pubkey) -> Result<()> {
         require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
         
         let match_account = &mut ctx.accounts.match_account;
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
         require!(match_account.status == MatchStatus::Completed, BattleError::MatchNotCompleted);
         
-        // Get all necessary values before mutable borrow
-        let winner = match_account.winner.as_ref().unwrap().clone();
-        let total_winning_bets = if winner == match_account.fighter1 {
-            match_account.total_bets_fighter1
-        } else {
-            match_account.total_bets_fighter2
+        let bet = &mut ctx.accounts.bet;
+        require!(!bet.claimed, BattleError::AlreadyClaimed);
+        require!(bet.amount > 0, BattleError::BetCancelled);
+        require!(match_account.placement(&bet.fighter).is_some(), BattleError::NotAWinner);
+        
+        // Calculate prize share for the bet's placement with overflow protection
+        let prize_share = match_account.prize_share(bet.amount, &bet.fighter)?;
+        let total_payout = bet.amount.checked_add(prize_share)
+            .ok_or(BattleError::Overflow)?;
+        
+        let token = match match_account.bet_mint {
+            Some(_) => Some(token_accounts(&ctx.accounts.bet_mint, &ctx.accounts.vault, &ctx.accounts.token_program)?),
+            None => None,
         };
-        let prize_pool = match_account.prize_pool;
-        
-        // Find the specific bettor in the bet list
-        let mut found = false;
-        let mut total_claimed = 0;
-        
-        for bet in match_account.bets.iter_mut() {
-            if !bet.claimed && bet.fighter == winner && bet.bettor == bettor_pubkey {
-                found = true;
-                
-                // Find the bettor account in remaining accounts
-                let mut bettor_account_opt = None;
-                for account in ctx.remaining_accounts.iter() {
-                    if account.key() == bettor_pubkey {
-                        bettor_account_opt = Some(account);
-                        break;
-                    }
-                }
-                
-                let bettor_account = bettor_account_opt
-                    .ok_or(error!(BattleError::InvalidRemainingAccounts))?;
-                
-                // Calculate prize share with overflow protection
-                let prize_share = calculate_prize_share(bet.amount, prize_pool, total_winning_bets)?;
-                let total_payout = bet.amount.checked_add(prize_share)
-                    .ok_or(BattleError::Overflow)?;
-                
-                // Transfer prize directly to bettor
-                let house_wallet_info = ctx.accounts.house_wallet.to_account_info();
-                let transfer_result = house_wallet_info.try_borrow_mut_lamports();
-                if let Err(err) = transfer_result {
-                    msg!("Failed to borrow house wallet lamports: {}", err);
-                    return err!(BattleError::TransferFailed);
-                }
-                
-                let receiver_result = bettor_account.try_borrow_mut_lamports();
-                if let Err(err) = receiver_result {
-                    msg!("Failed to borrow bettor account lamports: {}", err);
-                    return err!(BattleError::TransferFailed);
-                }
-                
-                let house_lamports = &mut **transfer_result.unwrap();
-                if *house_lamports < total_payout {
-                    msg!(
-                        "Insufficient funds in house wallet to pay prize to bettor: {}, Amount: {}",
-                        bet.bettor,
-                        total_payout
-                    );
-                    return err!(BattleError::InsufficientFunds);
-                }
-                
-                *house_lamports -= total_payout;
-                **receiver_result.unwrap() += total_payout;
-                
-                // Mark as claimed only if transfer was successful
-                bet.claimed = true;
-                total_claimed += total_payout;
-                
-                msg!(
-                    "Prize reclaimed - Bettor: {}, Amount: {}, Prize Share: {}",
-                    bet.bettor,
-                    bet.amount,
-                    prize_share
-                );
-                
-                break;
-            }
-        }
+        // Token payouts go to the bettor's token account for the bet mint
+        let destination = match token {
+            Some(_) => ctx.accounts.bettor_token_account.as_ref()
+                .ok_or(BattleError::MissingTokenAccounts)?
+                .to_account_info(),
+            None => ctx.accounts.bettor.to_account_info(),
+        };
+        pay_bettor(match_account, token, &ctx.accounts.escrow, bet.bettor, &destination, total_payout)?;
         
-        require!(found, BattleError::NoBet);
+        // Mark as claimed only if transfer was successful
+        bet.claimed = true;
         
-        if total_claimed > 0 {
-            msg!(
-                "Prize reclaimed for bettor: {}, Amount: {}",
-                bettor_pubkey,
-                total_claimed
-            );
-        } else {
-            msg!("No prize claimed");
-        }
+        emit!(PrizePaid {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            bettor: bet.bettor,
+            amount: total_payout,
+            prize_share,
+        });
+        
+        msg!(
+            "Prize reclaimed for bettor: {}, Amount: {}, Prize Share: {}",
+            bettor_pubkey,
+            total_payout,
+            prize_share
+        );
+
+        Ok(())
+    }
+
+    pub fn claim_winnings(ctx: Context<ClaimWinnings>, match_id: String) -> Result<()> {
+        require!(!ctx.accounts.house_wallet.paused, BattleError::ProgramPaused);
+        
+        let match_account = &mut ctx.accounts.match_account;
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        require!(match_account.status == MatchStatus::Completed, BattleError::MatchNotCompleted);
+        
+        let bet = &mut ctx.accounts.bet;
+        require!(!bet.claimed, BattleError::AlreadyClaimed);
+        require!(bet.amount > 0, BattleError::BetCancelled);
+        require!(match_account.placement(&bet.fighter).is_some(), BattleError::NotAWinner);
+        
+        // Calculate prize share for the bet's placement with overflow protection
+        let prize_share = match_account.prize_share(bet.amount, &bet.fighter)?;
+        let total_payout = bet.amount.checked_add(prize_share)
+            .ok_or(BattleError::Overflow)?;
+        
+        let token = match match_account.bet_mint {
+            Some(_) => Some(token_accounts(&ctx.accounts.bet_mint, &ctx.accounts.vault, &ctx.accounts.token_program)?),
+            None => None,
+        };
+        // Token payouts go to the bettor's token account for the bet mint
+        let destination = match token {
+            Some(_) => ctx.accounts.bettor_token_account.as_ref()
+                .ok_or(BattleError::MissingTokenAccounts)?
+                .to_account_info(),
+            None => ctx.accounts.bettor.to_account_info(),
+        };
+        pay_bettor(match_account, token, &ctx.accounts.escrow, bet.bettor, &destination, total_payout)?;
+        
+        bet.claimed = true;
+        
+        emit!(PrizePaid {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            bettor: bet.bettor,
+            amount: total_payout,
+            prize_share,
+        });
+        
+        msg!(
+            "Winnings claimed - Match: {}, Bettor: {}, Amount: {}, Prize Share: {}",
+            match_id,
+            bet.bettor,
+            total_payout,
+            prize_share
+        );
+        Ok(())
+    }
+
+    pub fn claim_bet_refund(ctx: Context<ClaimBetRefund>, match_id: String) -> Result<()> {
+        let match_account = &mut ctx.accounts.match_account;
+        // Like expire_match, stakes owed back by refunded, drawn or no-contest matches stay recoverable while paused
+        require!(
+            !ctx.accounts.house_wallet.paused || match_account.is_refundable(),
+            BattleError::ProgramPaused
+        );
+        
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        require!(match_account.is_refundable(), BattleError::NotRefundable);
+        
+        let bet = &mut ctx.accounts.bet;
+        require!(!bet.claimed, BattleError::AlreadyClaimed);
+        require!(bet.amount > 0, BattleError::BetCancelled);
+        let refund = match_account.refund_amount(bet.amount)?;
+        
+        let token = match match_account.bet_mint {
+            Some(_) => Some(token_accounts(&ctx.accounts.bet_mint, &ctx.accounts.vault, &ctx.accounts.token_program)?),
+            None => None,
+        };
+        // Token payouts go to the bettor's token account for the bet mint
+        let destination = match token {
+            Some(_) => ctx.accounts.bettor_token_account.as_ref()
+                .ok_or(BattleError::MissingTokenAccounts)?
+                .to_account_info(),
+            None => ctx.accounts.bettor.to_account_info(),
+        };
+        pay_bettor(match_account, token, &ctx.accounts.escrow, bet.bettor, &destination, refund)?;
+        
+        bet.claimed = true;
+        
+        emit!(RefundPaid {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            bettor: bet.bettor,
+            amount: refund,
+        });
+        
+        msg!(
+            "Refund claimed - Match: {}, Bettor: {}, Amount: {}",
+            match_id,
+            bet.bettor,
+            refund
+        );
+        Ok(())
+    }
 
+    pub fn close_bet_account(ctx: Context<CloseBetAccount>, match_id: String) -> Result<()> {
+        let match_account = &mut ctx.accounts.match_account;
+        require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+        
+        // A bet is settled once it has been paid out or cancelled, or once it missed the podium of a completed match
+        let bet = &ctx.accounts.bet;
+        let settled = bet.claimed || bet.amount == 0 || (
+            match_account.status == MatchStatus::Completed &&
+            match_account.placement(&bet.fighter).is_none()
+        );
+        require!(settled, BattleError::BetNotSettled);
+        // Closing a bet the payout pages have not walked yet would leave a gap in front of the cursor
+        require!(bet.index < match_account.settled_cursor, BattleError::BetNotSettled);
+        
+        match_account.closed_bet_count += 1;
+        
+        emit!(BetClosed {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            bet: bet.key(),
+            bettor: bet.bettor,
+            fighter: bet.fighter.clone(),
+        });
+        msg!("Bet account closed - Match: {}, Bettor: {}", match_id, bet.bettor);
         Ok(())
     }
 
     pub fn close_match_account(ctx: Context<CloseMatchAccount>, match_id: String) -> Result<()> {
-        require!(is_authorized(ctx.accounts.authority.key(), &ctx.accounts.house_wallet), BattleError::Unauthorized);
+        require!(has_role(ctx.accounts.authority.key(), &ctx.accounts.house_wallet, Role::Operator), BattleError::Unauthorized);
         
         let match_account = &ctx.accounts.match_account;
         require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
         
         // Match must be completed or refunded to be closed
         require!(
-            match_account.status == MatchStatus::Completed || match_account.status == MatchStatus::Refund,
+            match_account.status == MatchStatus::Completed || match_account.is_refundable(),
             BattleError::MatchNotFinalized
         );
         
         // If completed, all prizes must be claimed
         if match_account.status == MatchStatus::Completed {
-            let all_claimed = match_account.bets.iter().all(|bet| {
-                bet.claimed || 
-                match_account.winner.as_ref().map_or(false, |winner| bet.fighter != *winner)
-            });
+            let winning_bet_count: u32 = match_account.placements.iter()
+                .filter_map(|placement| match_account.fighter(&placement.fighter))
+                .map(|pool| pool.bet_count)
+                .sum();
             
-            require!(all_claimed, BattleError::UnclaimedPrizes);
+            require!(match_account.claimed_count >= winning_bet_count, BattleError::UnclaimedPrizes);
         }
         
         // If refunded, all refunds must be claimed
-        if match_account.status == MatchStatus::Refund {
-            let all_claimed = match_account.bets.iter().all(|bet| bet.claimed);
+        if match_account.is_refundable() {
+            let all_claimed = match_account.claimed_count == match_account.total_bet_count();
             require!(all_claimed, BattleError::UnclaimedRefunds);
         }
         
+        // Every bet account, cancelled ones included, must be closed first so no bettor rent is stranded
+        require!(
+            match_account.closed_bet_count == match_account.next_bet_index,
+            BattleError::OpenBetAccounts
+        );
+        
+        // Sweep whatever is left in a token vault to the treasury and reclaim its rent
+        let swept;
+        if match_account.bet_mint.is_some() {
+            let (bet_mint, vault, token_program) = token_accounts(
+                &ctx.accounts.bet_mint,
+                &ctx.accounts.vault,
+                &ctx.accounts.token_program,
+            )?;
+            let treasury_token_account = ctx.accounts.treasury_token_account.as_ref()
+                .ok_or(BattleError::MissingTokenAccounts)?
+                .to_account_info();
+            check_token_destination(&treasury_token_account, match_account.treasury, bet_mint.key())?;
+            let match_bump = [match_account.bump];
+            let match_seeds: &[&[u8]] = &[b"match", match_id.as_bytes(), &match_bump];
+            
+            swept = vault.amount;
+            if swept > 0 {
+                transfer_from_vault(
+                    token_program,
+                    vault,
+                    bet_mint,
+                    match_account.to_account_info(),
+                    match_seeds,
+                    treasury_token_account,
+                    swept,
+                )?;
+                msg!("Swept {} leftover tokens to treasury", swept);
+            }
+            
+            close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: match_account.to_account_info(),
+                },
+                &[match_seeds],
+            ))?;
+        } else {
+            // The escrow's rent goes back to the authority when it closes
+            let escrow = escrow_info(&ctx.accounts.escrow)?;
+            swept = escrow_balance(&escrow)?;
+            if swept > 0 {
+                transfer_from_escrow(&escrow, &ctx.accounts.treasury, swept)?;
+                msg!("Swept {} leftover lamports to treasury", swept);
+            }
+        }
+        
+        emit!(MatchClosed {
+            match_account: match_account.key(),
+            match_id: match_id.clone(),
+            treasury: match_account.treasury,
+            bet_mint: match_account.bet_mint,
+            swept,
+        });
         msg!("Match account closed: {}", match_id);
         Ok(())
     }
@@ -647,49 +1314,48 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(match_id: String, fighter: String, amount: u64)]
-pub struct PlaceBet<'info> {
+pub struct MigrateHouseWallet<'info> {
+    /// CHECK: Still in the pre-config layout, so it is parsed by hand once the seeds and owner match
     #[account(
         mut,
-        seeds = [b"match", match_id.as_bytes()],
-        bump = match_account.bump
+        seeds = [b"house"],
+        bump,
+        owner = crate::ID
     )]
-    pub match_account: Account<'info, MatchAccount>,
-    
-    #[account(mut)]
-    pub house_wallet: Account<'info, HouseWallet>,
+    pub house_wallet: UncheckedAccount<'info>,
     
     #[account(mut)]
-    pub bettor: Signer<'info>,
+    pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(match_id: String, winner: String)]
-pub struct EndMatch<'info> {
+#[instruction(match_id: String)]
+pub struct SettleLegacyMatch<'info> {
+    /// CHECK: Still in the layout with bets stored inline, so it is parsed by hand once the seeds and owner match
     #[account(
         mut,
         seeds = [b"match", match_id.as_bytes()],
-        bump = match_account.bump
+        bump,
+        owner = crate::ID
     )]
-    pub match_account: Account<'info, MatchAccount>,
+    pub match_account: UncheckedAccount<'info>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
     pub house_wallet: Account<'info, HouseWallet>,
     
-    /// CHECK: This account will receive the fee
     #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    
     pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(match_id: String)]
-pub struct ClaimPrize<'info> {
+#[instruction(match_id: String, fighter: String, amount: u64)]
+pub struct PlaceBet<'info> {
     #[account(
         mut,
         seeds = [b"match", match_id.as_bytes()],
@@ -697,84 +1363,65 @@ pub struct ClaimPrize<'info> {
     )]
     pub match_account: Account<'info, MatchAccount>,
     
-    #[account(mut)]
-    pub house_wallet: Account<'info, HouseWallet>,
-    
-    /// CHECK: This account will receive the fee
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + Bet::SPACE,
+        seeds = [b"bet", match_account.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
     
-    pub authority: Signer<'info>,
+    /// A position on a second fighter, when the match allows hedging
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + Bet::SPACE,
+        seeds = [b"bet", match_account.key().as_ref(), bettor.key().as_ref(), fighter.as_bytes()],
+        bump
+    )]
+    pub hedge_bet: Option<Account<'info, Bet>>,
     
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-#[instruction(match_id: String)]
-pub struct ClaimRefund<'info> {
     #[account(
         mut,
-        seeds = [b"match", match_id.as_bytes()],
-        bump = match_account.bump
+        seeds = [b"house"],
+        bump = house_wallet.bump
     )]
-    pub match_account: Account<'info, MatchAccount>,
-    
-    #[account(mut)]
     pub house_wallet: Account<'info, HouseWallet>,
     
-    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
     
     pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct SetPauseState<'info> {
-    #[account(mut)]
-    pub house_wallet: Account<'info, HouseWallet>,
     
-    pub authority: Signer<'info>,
-}
-
-#[derive(Accounts)]
-#[instruction(status: String, match_id: String)]
-pub struct UpdateMatchStatus<'info> {
+    /// SOL matches only
     #[account(
         mut,
-        seeds = [b"match", match_id.as_bytes()],
-        bump = match_account.bump
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
     )]
-    pub match_account: Account<'info, MatchAccount>,
+    pub escrow: Option<Account<'info, Escrow>>,
     
-    #[account(mut)]
-    pub house_wallet: Account<'info, HouseWallet>,
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
     
-    pub authority: Signer<'info>,
-}
-
-#[derive(Accounts)]
-#[instruction(match_id: String, fighter1: String, fighter2: String)]
-pub struct CreateMatchAccount<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + MatchAccount::SPACE,
-        seeds = [b"match", match_id.as_bytes()],
-        bump
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
     )]
-    pub match_account: Account<'info, MatchAccount>,
-    
-    #[account(mut)]
-    pub house_wallet: Account<'info, HouseWallet>,
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
     
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub bettor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 #[derive(Accounts)]
 #[instruction(match_id: String)]
-pub struct EmergencyRefund<'info> {
+pub struct WithdrawBet<'info> {
     #[account(
         mut,
         seeds = [b"match", match_id.as_bytes()],
@@ -782,103 +1429,1509 @@ pub struct EmergencyRefund<'info> {
     )]
     pub match_account: Account<'info, MatchAccount>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        has_one = match_account @ BattleError::InvalidBetAccount,
+        has_one = bettor @ BattleError::InvalidBettorAccount
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
     pub house_wallet: Account<'info, HouseWallet>,
     
-    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub bettor: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    /// The wallet's main bet, when withdrawing from a hedge bet
+    #[account(
+        mut,
+        seeds = [b"bet", match_account.key().as_ref(), bettor.key().as_ref()],
+        bump = main_bet.bump
+    )]
+    pub main_bet: Option<Account<'info, Bet>>,
+    
+    /// CHECK: This account will receive the cancellation fee
+    #[account(mut, address = match_account.treasury @ BattleError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub bettor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct EndMatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    /// CHECK: This account will receive the fee
+    #[account(mut, address = match_account.treasury @ BattleError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    /// Tournament matches only
+    #[account(
+        mut,
+        address = match_account.tournament.unwrap_or_default() @ BattleError::InvalidTournament
+    )]
+    pub tournament: Option<Account<'info, Tournament>>,
+    
+    /// The bracket match the winner advances to; omitted for the final
+    #[account(mut)]
+    pub next_match: Option<Account<'info, MatchAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct AdvanceBracket<'info> {
+    #[account(
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        address = match_account.tournament.unwrap_or_default() @ BattleError::InvalidTournament
+    )]
+    pub tournament: Account<'info, Tournament>,
+    
+    /// The bracket match the winner advances to; omitted for the final
+    #[account(mut)]
+    pub next_match: Option<Account<'info, MatchAccount>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct ClaimPrize<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    /// CHECK: This account will receive the fee
+    #[account(mut, address = match_account.treasury @ BattleError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRole<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseState<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(status: String, match_id: String)]
+pub struct UpdateMatchStatus<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct SetPayoutSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct SetBetLimits<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct StartBattle<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct ExpireMatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct CreateMatchAccount<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MatchAccount::SPACE,
+        seeds = [b"match", match_id.as_bytes()],
+        bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Escrow::SPACE,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct CreateTokenMatchAccount<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MatchAccount::SPACE,
+        seeds = [b"match", match_id.as_bytes()],
+        bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(mint::token_program = token_program)]
+    pub bet_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump,
+        token::mint = bet_mint,
+        token::authority = match_account,
+        token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tournament_id: String, pool_match_id: String)]
+pub struct CreateTournament<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Tournament::SPACE,
+        seeds = [b"tournament", tournament_id.as_bytes()],
+        bump
+    )]
+    pub tournament: Account<'info, Tournament>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MatchAccount::SPACE,
+        seeds = [b"match", pool_match_id.as_bytes()],
+        bump
+    )]
+    pub winner_pool: Account<'info, MatchAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Escrow::SPACE,
+        seeds = [b"escrow", winner_pool.key().as_ref()],
+        bump
+    )]
+    pub winner_pool_escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tournament_id: String, slot: u8, match_id: String)]
+pub struct CreateTournamentMatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"tournament", tournament_id.as_bytes()],
+        bump = tournament.bump
+    )]
+    pub tournament: Account<'info, Tournament>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MatchAccount::SPACE,
+        seeds = [b"match", match_id.as_bytes()],
+        bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Escrow::SPACE,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct EmergencyRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct TransferFromHouseWallet<'info> {
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// CHECK: This account will receive the funds
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String, bettor_pubkey: Pubkey)]
+pub struct ReclaimPrize<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        has_one = match_account @ BattleError::InvalidBetAccount,
+        has_one = bettor @ BattleError::InvalidBettorAccount
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    /// CHECK: This account will receive the prize
+    #[account(mut, address = bettor_pubkey @ BattleError::InvalidBettorAccount)]
+    pub bettor: AccountInfo<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub bettor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct ClaimWinnings<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        has_one = match_account @ BattleError::InvalidBetAccount,
+        has_one = bettor @ BattleError::InvalidBettorAccount
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub bettor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct ClaimBetRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        has_one = match_account @ BattleError::InvalidBetAccount,
+        has_one = bettor @ BattleError::InvalidBettorAccount
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
+    pub house_wallet: Account<'info, HouseWallet>,
+    
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub bettor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+#[instruction(match_id: String)]
+pub struct CloseBetAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        has_one = match_account @ BattleError::InvalidBetAccount,
+        has_one = bettor @ BattleError::InvalidBettorAccount,
+        close = bettor
+    )]
+    pub bet: Account<'info, Bet>,
+    
+    /// CHECK: The bettor who paid for the bet account; receives its rent
+    #[account(mut)]
+    pub bettor: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
-pub struct TransferFromHouseWallet<'info> {
-    #[account(mut)]
+#[instruction(match_id: String)]
+pub struct CloseMatchAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"match", match_id.as_bytes()],
+        bump = match_account.bump,
+        close = authority
+    )]
+    pub match_account: Account<'info, MatchAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"house"],
+        bump = house_wallet.bump
+    )]
     pub house_wallet: Account<'info, HouseWallet>,
     
+    /// CHECK: This account receives whatever is left in the match's funds
+    #[account(mut, address = match_account.treasury @ BattleError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
     
-    /// CHECK: This account will receive the funds
+    /// SOL matches only
+    #[account(
+        mut,
+        seeds = [b"escrow", match_account.key().as_ref()],
+        bump = escrow.bump,
+        close = authority
+    )]
+    pub escrow: Option<Account<'info, Escrow>>,
+    
+    /// Token matches only
+    #[account(constraint = match_account.bet_mint == Some(bet_mint.key()) @ BattleError::InvalidBetMint)]
+    pub bet_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", match_account.key().as_ref()],
+        bump = match_account.vault_bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
     #[account(mut)]
-    pub recipient: AccountInfo<'info>,
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[account]
+pub struct HouseWallet {
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub bump: u8,
+    pub paused: bool,
+    pub initialized: bool,
+    pub fee_bps: u16,
+    pub max_fee: u64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub treasury: Pubkey,
+    pub operators: Vec<Pubkey>,
+    pub resolvers: Vec<Pubkey>,
+    pub treasurers: Vec<Pubkey>,
+    pub pausers: Vec<Pubkey>,
+    pub draw_handling: DrawHandling,
+    pub cancel_fee_bps: u16,
+    pub hedging_policy: HedgingPolicy,
+}
+
+impl HouseWallet {
+    pub const SPACE: usize = 
+        32 + // authority
+        1 + 32 + // pending_authority
+        1 + // bump
+        1 + // paused
+        1 + // initialized
+        2 + // fee_bps
+        8 + // max_fee
+        8 + // min_bet
+        8 + // max_bet
+        32 + // treasury
+        4 * (4 + 32 * HouseWallet::MAX_ROLE_MEMBERS) + // operators, resolvers, treasurers, pausers
+        1 + // draw_handling
+        2 + // cancel_fee_bps
+        1; // hedging_policy
+
+    pub const DEFAULT_FEE_BPS: u16 = 500; // 5% of losing bets
+    pub const DEFAULT_MAX_FEE: u64 = u64::MAX;
+    pub const DEFAULT_MIN_BET: u64 = 50_000_000; // 0.05 SOL
+    pub const DEFAULT_MAX_BET: u64 = u64::MAX;
+    pub const MAX_ROLE_MEMBERS: usize = 5;
+
+    pub fn role_members(&self, role: Role) -> &Vec<Pubkey> {
+        match role {
+            Role::Operator => &self.operators,
+            Role::Resolver => &self.resolvers,
+            Role::Treasurer => &self.treasurers,
+            Role::Pauser => &self.pausers,
+        }
+    }
+
+    pub fn role_members_mut(&mut self, role: Role) -> &mut Vec<Pubkey> {
+        match role {
+            Role::Operator => &mut self.operators,
+            Role::Resolver => &mut self.resolvers,
+            Role::Treasurer => &mut self.treasurers,
+            Role::Pauser => &mut self.pausers,
+        }
+    }
+}
+
+/// House wallet layout deployed before fees, limits and roles were configurable;
+/// only read by `migrate_house_wallet`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyHouseWallet {
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub paused: bool,
+    pub initialized: bool,
+}
+
+/// Match layout deployed before bets moved into their own PDAs, with stakes held by
+/// the house wallet; only read by `settle_legacy_match`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyMatchAccount {
+    pub match_id: String,
+    pub fighter1: String,
+    pub fighter2: String,
+    pub total_bets_fighter1: u64,
+    pub total_bets_fighter2: u64,
+    pub status: MatchStatus,
+    pub winner: Option<String>,
+    pub prize_pool: u64,
+    pub bets: Vec<LegacyBet>,
+    pub bump: u8,
+}
+
+impl LegacyMatchAccount {
+    pub const SPACE: usize = 
+        32 + // match_id
+        10 + // fighter1
+        10 + // fighter2
+        8 + // total_bets_fighter1
+        8 + // total_bets_fighter2
+        1 + // status
+        11 + // winner (Option<String>)
+        8 + // prize_pool
+        4 + // Vec length (u32)
+        (80 * 100) + // Space for up to 100 bets (~8,000 bytes)
+        1;  // bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyBet {
+    pub bettor: Pubkey,
+    pub amount: u64,
+    pub fighter: String,
+    pub claimed: bool,
+}
+
+#[account]
+pub struct MatchAccount {
+    pub match_id: String,
+    pub fighters: Vec<FighterPool>,
+    pub status: MatchStatus,
+    pub winner: Option<String>,
+    pub prize_pool: u64,
+    pub payout_split: Vec<u16>,
+    pub placements: Vec<Placement>,
+    pub fee_bps: u16,
+    pub max_fee: u64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub max_wallet_total: u64,
+    pub max_fighter_pool: u64,
+    pub treasury: Pubkey,
+    pub claimed_count: u32,
+    /// Stakes, prize shares and refunds paid out to bettors
+    pub paid_out: u64,
+    pub closed_bet_count: u32,
+    /// Index the next new bet position gets
+    pub next_bet_index: u32,
+    /// Batch payouts have walked every bet below this index
+    pub settled_cursor: u32,
+    pub bet_mint: Option<Pubkey>,
+    pub vault_bump: u8,
+    pub betting_opens_at: i64,
+    pub betting_closes_at: i64,
+    pub expected_end_at: i64,
+    pub resolution_deadline: i64,
+    pub draw_handling: DrawHandling,
+    pub cancel_fee_bps: u16,
+    pub hedging_policy: HedgingPolicy,
+    pub tournament: Option<Pubkey>,
+    pub bracket_slot: Option<u8>,
+    pub bump: u8,
+}
+
+impl MatchAccount {
+    pub const SPACE: usize = 
+        4 + 32 + // match_id
+        4 + FighterPool::SPACE * MatchAccount::MAX_FIGHTERS + // fighters
+        1 + // status
+        1 + 4 + 10 + // winner (Option<String>)
+        8 + // prize_pool
+        4 + 2 * MatchAccount::MAX_PLACES + // payout_split
+        4 + Placement::SPACE * MatchAccount::MAX_PLACES + // placements
+        2 + // fee_bps
+        8 + // max_fee
+        8 + // min_bet
+        8 + // max_bet
+        8 + // max_wallet_total
+        8 + // max_fighter_pool
+        32 + // treasury
+        4 + // claimed_count
+        8 + // paid_out
+        4 + // closed_bet_count
+        4 + // next_bet_index
+        4 + // settled_cursor
+        1 + 32 + // bet_mint
+        1 + // vault_bump
+        8 + // betting_opens_at
+        8 + // betting_closes_at
+        8 + // expected_end_at
+        8 + // resolution_deadline
+        1 + // draw_handling
+        2 + // cancel_fee_bps
+        1 + // hedging_policy
+        1 + 32 + // tournament
+        1 + 1 + // bracket_slot
+        1;  // bump
+
+    pub const MAX_FIGHTERS: usize = 16;
+    pub const MAX_PLACES: usize = 5;
+
+    pub fn fighter(&self, name: &str) -> Option<&FighterPool> {
+        self.fighters.iter().find(|pool| pool.name == name)
+    }
+
+    pub fn fighter_mut(&mut self, name: &str) -> Option<&mut FighterPool> {
+        self.fighters.iter_mut().find(|pool| pool.name == name)
+    }
+
+    /// Podium spot a fighter finished in, if it earned a payout.
+    pub fn placement(&self, fighter: &str) -> Option<&Placement> {
+        self.placements.iter().find(|placement| placement.fighter == fighter)
+    }
+
+    /// Prize owed on top of the stake for a bet on a placed fighter.
+    pub fn prize_share(&self, bet_amount: u64, fighter: &str) -> Result<u64> {
+        let placement = self.placement(fighter).ok_or(BattleError::NotAWinner)?;
+        let total_placed_bets = self.fighter(fighter).map_or(0, |pool| pool.total_bets);
+        calculate_prize_share(bet_amount, placement.prize_pool, total_placed_bets)
+    }
+
+    /// False while a bracket match is still waiting on a feeder winner.
+    pub fn fighters_decided(&self) -> bool {
+        self.fighters.iter().all(|pool| !pool.name.is_empty())
+    }
+
+    /// Stakes across every fighter.
+    pub fn total_pool(&self) -> u64 {
+        self.fighters.iter().map(|pool| pool.total_bets).sum()
+    }
+
+    pub fn total_bet_count(&self) -> u32 {
+        self.fighters.iter().map(|pool| pool.bet_count).sum()
+    }
+
+    /// Everything owed to bettors once the match is settled, before shares are floored.
+    pub fn settlement_total(&self) -> u64 {
+        match self.status {
+            // The whole prize pool rather than the tier splits, so dust from splitting it reaches the last claimer
+            MatchStatus::Completed => self.placements.iter()
+                .filter_map(|placement| self.fighter(&placement.fighter))
+                .map(|pool| pool.total_bets)
+                .sum::<u64>()
+                + self.prize_pool,
+            MatchStatus::Draw | MatchStatus::NoContest => self.prize_pool,
+            _ => self.total_pool(),
+        }
+    }
+
+    /// Bets that are owed a payout once the match is settled.
+    pub fn payable_bet_count(&self) -> u32 {
+        match self.status {
+            MatchStatus::Completed => self.placements.iter()
+                .filter_map(|placement| self.fighter(&placement.fighter))
+                .map(|pool| pool.bet_count)
+                .sum(),
+            _ => self.total_bet_count(),
+        }
+    }
+
+    /// Rounding remainder owed on top of `amount` when it is the match's last payout.
+    pub fn payout_remainder(&self, amount: u64) -> u64 {
+        if self.claimed_count + 1 < self.payable_bet_count() {
+            return 0;
+        }
+        self.settlement_total()
+            .saturating_sub(self.paid_out)
+            .saturating_sub(amount)
+    }
+
+    pub fn record_payout(&mut self, amount: u64) -> Result<()> {
+        self.claimed_count += 1;
+        self.paid_out = self.paid_out.checked_add(amount).ok_or(BattleError::Overflow)?;
+        Ok(())
+    }
+
+    /// Refund, Draw and NoContest matches pay stakes back instead of prizes.
+    pub fn is_refundable(&self) -> bool {
+        matches!(self.status, MatchStatus::Refund | MatchStatus::Draw | MatchStatus::NoContest)
+    }
+
+    /// Amount owed back on a stake; draws share the refund pool left after any fee.
+    pub fn refund_amount(&self, bet_amount: u64) -> Result<u64> {
+        match self.status {
+            MatchStatus::Draw | MatchStatus::NoContest => calculate_prize_share(
+                bet_amount,
+                self.prize_pool,
+                self.total_pool(),
+            ),
+            _ => Ok(bet_amount),
+        }
+    }
+}
+
+/// One fighter in a match and the stakes placed on it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FighterPool {
+    pub name: String,
+    pub total_bets: u64,
+    pub bet_count: u32,
+}
+
+impl FighterPool {
+    pub const SPACE: usize = 
+        4 + 10 + // name
+        8 + // total_bets
+        4;  // bet_count
+}
+
+/// A fighter that finished on the podium and the prize set aside for its backers.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Placement {
+    pub fighter: String,
+    pub prize_pool: u64,
+}
+
+impl Placement {
+    pub const SPACE: usize = 
+        4 + 10 + // fighter
+        8;  // prize_pool
+}
+
+#[account]
+pub struct Bet {
+    pub match_account: Pubkey,
+    pub bettor: Pubkey,
+    pub amount: u64,
+    pub fighter: String,
+    pub claimed: bool,
+    pub bump: u8,
+    /// Held at the wallet's fighter-specific address rather than its main bet
+    pub hedge: bool,
+    /// Stake across the wallet's main and hedge bets; kept on the main bet
+    pub wallet_total: u64,
+    /// Open hedge bets; kept on the main bet
+    pub hedge_count: u8,
+    /// Position in the match's settlement order
+    pub index: u32,
+}
+
+impl Bet {
+    pub const SPACE: usize = 
+        32 + // match_account
+        32 + // bettor
+        8 + // amount
+        4 + 10 + // fighter
+        1 + // claimed
+        1 + // bump
+        1 + // hedge
+        8 + // wallet_total
+        1 + // hedge_count
+        4;  // index
+}
+
+/// Holds a SOL match's stakes apart from every other match.
+#[account]
+pub struct Escrow {
+    pub match_account: Pubkey,
+    pub bump: u8,
+}
+
+impl Escrow {
+    pub const SPACE: usize = 
+        32 + // match_account
+        1;  // bump
+}
+
+/// A single-elimination bracket. Slot 0 is the final and slot k is fed by
+/// slots 2k+1 and 2k+2, so the first round is the last half of `matches`.
+#[account]
+pub struct Tournament {
+    pub tournament_id: String,
+    pub fighters: Vec<String>,
+    pub matches: Vec<Pubkey>,
+    pub winner_pool: Pubkey,
+    pub champion: Option<String>,
+    pub bump: u8,
+}
+
+impl Tournament {
+    pub const SPACE: usize = 
+        4 + 32 + // tournament_id
+        4 + (4 + 10) * MatchAccount::MAX_FIGHTERS + // fighters
+        4 + 32 * (MatchAccount::MAX_FIGHTERS - 1) + // matches
+        32 + // winner_pool
+        1 + 4 + 10 + // champion
+        1;  // bump
+
+    /// Slot a match's winner advances to, and the fighter position they take there.
+    pub fn next_slot(slot: usize) -> Option<(usize, usize)> {
+        if slot == 0 {
+            None
+        } else {
+            Some(((slot - 1) / 2, (slot - 1) % 2))
+        }
+    }
+
+    /// Fighters for a slot: seeded pairs in the first round, undecided afterwards.
+    pub fn slot_fighters(&self, slot: usize) -> Vec<String> {
+        let first_round = self.fighters.len() / 2 - 1;
+        if slot >= first_round {
+            let seed = 2 * (slot - first_round);
+            self.fighters[seed..seed + 2].to_vec()
+        } else {
+            vec![String::new(), String::new()]
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum MatchStatus {
+    Preparation,
+    Battle,
+    Completed,
+    Refund,
+    Draw,
+    NoContest,
+}
+
+/// Outcome reported by the resolver in `end_match`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum MatchResult {
+    Winner(String),
+    /// Fighters in finishing order, one per entry in the match's payout split
+    Placements(Vec<String>),
+    Draw,
+    NoContest,
+}
+
+/// How stakes are returned when a match ends in a draw or no contest.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum DrawHandling {
+    /// Every bettor gets their full stake back
+    FullRefund,
+    /// The match fee is taken on the whole pool and the rest is refunded pro rata
+    RefundMinusFee,
+}
+
+/// Whether a wallet may back more than one fighter in the same match.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum HedgingPolicy {
+    /// Repeat bets must be on the fighter the wallet already backs
+    SameFighterOnly,
+    /// Bets on other fighters are held in separate hedge bets
+    AllowHedging,
+}
+
+/// Unix timestamps bounding a match's betting window and resolution.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct MatchSchedule {
+    pub betting_opens_at: i64,
+    pub betting_closes_at: i64,
+    pub expected_end_at: i64,
+    /// After this, the match can no longer be ended and anyone may move it to Refund
+    pub resolution_deadline: i64,
+}
+
+/// Delegated permissions; the house authority implicitly holds every role.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Role {
+    /// Creates matches, advances status, pushes payouts and closes matches
+    Operator,
+    /// Ends matches and triggers emergency refunds
+    Resolver,
+    /// Moves funds out of the house wallet
+    Treasurer,
+    /// Pauses and unpauses the program
+    Pauser,
+}
+
+#[event]
+pub struct MatchCreated {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub fighters: Vec<String>,
+    pub bet_mint: Option<Pubkey>,
+    pub tournament: Option<Pubkey>,
+    pub betting_opens_at: i64,
+    pub betting_closes_at: i64,
+    pub resolution_deadline: i64,
+}
+
+#[event]
+pub struct BetPlaced {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub bettor: Pubkey,
+    pub fighter: String,
+    pub amount: u64,
+    pub bet_total: u64,
+    pub fighter_pool: u64,
+    pub total_pool: u64,
+}
+
+#[event]
+pub struct BetWithdrawn {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub bettor: Pubkey,
+    pub fighter: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct StatusChanged {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub previous_status: MatchStatus,
+    pub status: MatchStatus,
+}
+
+#[event]
+pub struct MatchEnded {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub status: MatchStatus,
+    pub winner: Option<String>,
+    pub placements: Vec<Placement>,
+    pub prize_pool: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct PrizePaid {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub bettor: Pubkey,
+    pub amount: u64,
+    pub prize_share: u64,
+}
+
+#[event]
+pub struct RefundPaid {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub bettor: Pubkey,
+    pub amount: u64,
+}
+
+/// The rounding remainder of a match's floored shares, paid with its last payout.
+#[event]
+pub struct RemainderPaid {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub paid_out: u64,
+}
+
+#[event]
+pub struct HouseWithdrawal {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct PauseChanged {
+    pub paused: bool,
+    pub authority: Pubkey,
 }
 
-#[derive(Accounts)]
-#[instruction(match_id: String)]
-pub struct CloseMatchAccount<'info> {
-    #[account(
-        mut,
-        seeds = [b"match", match_id.as_bytes()],
-        bump = match_account.bump,
-        close = authority
-    )]
-    pub match_account: Account<'info, MatchAccount>,
-    
-    #[account(mut)]
-    pub house_wallet: Account<'info, HouseWallet>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
 }
 
-#[account]
-pub struct HouseWallet {
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
     pub authority: Pubkey,
-    pub bump: u8,
-    pub paused: bool,
-    pub initialized: bool,
 }
 
-impl HouseWallet {
-    pub const SPACE: usize = 32 + 1 + 1 + 1; // 32 bytes for authority + bump + paused + initialized
+#[event]
+pub struct RoleChanged {
+    pub role: Role,
+    pub member: Pubkey,
+    /// False when the member was removed from the role
+    pub granted: bool,
 }
 
-#[account]
-pub struct MatchAccount {
+/// House-wide terms that newly created matches copy.
+#[event]
+pub struct ConfigUpdated {
+    pub fee_bps: u16,
+    pub max_fee: u64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub draw_handling: DrawHandling,
+    pub cancel_fee_bps: u16,
+    pub hedging_policy: HedgingPolicy,
+}
+
+#[event]
+pub struct TreasuryChanged {
+    pub previous_treasury: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct PayoutSplitChanged {
+    pub match_account: Pubkey,
     pub match_id: String,
-    pub fighter1: String,
-    pub fighter2: String,
-    pub total_bets_fighter1: u64,
-    pub total_bets_fighter2: u64,
-    pub status: MatchStatus,
-    pub winner: Option<String>,
-    pub prize_pool: u64,
-    pub bets: Vec<Bet>,
-    pub bump: u8,
+    pub payout_split: Vec<u16>,
 }
 
-impl MatchAccount {
-    pub const SPACE: usize = 
-        32 + // match_id
-        10 + // fighter1
-        10 + // fighter2
-        8 + // total_bets_fighter1
-        8 + // total_bets_fighter2
-        1 + // status
-        11 + // winner (Option<String>)
-        8 + // prize_pool
-        4 + // Vec length (u32)
-        (80 * 100) + // Space for up to 100 bets (~8,000 bytes)
-        1;  // bump
+#[event]
+pub struct BetLimitsChanged {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub max_wallet_total: u64,
+    pub max_fighter_pool: u64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct Bet {
+/// A fighter moved up the bracket; `next_match` is None when they won the final.
+#[event]
+pub struct BracketAdvanced {
+    pub tournament: Pubkey,
+    pub tournament_id: String,
+    pub slot: u8,
+    pub fighter: String,
+    pub next_match: Option<Pubkey>,
+}
+
+#[event]
+pub struct BetClosed {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub bet: Pubkey,
     pub bettor: Pubkey,
-    pub amount: u64,
     pub fighter: String,
-    pub claimed: bool,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum MatchStatus {
-    Preparation,
-    Battle,
-    Completed,
-    Refund,
+/// `swept` is what was left in the escrow (lamports) or vault (tokens) and went to the treasury.
+#[event]
+pub struct MatchClosed {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub treasury: Pubkey,
+    pub bet_mint: Option<Pubkey>,
+    pub swept: u64,
+}
+
+#[event]
+pub struct HouseWalletMigrated {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub rent_top_up: u64,
+}
+
+#[event]
+pub struct LegacyMatchSettled {
+    pub match_account: Pubkey,
+    pub match_id: String,
+    pub settled_count: u32,
+    pub total_paid: u64,
+    pub closed: bool,
 }
 
 #[error_code]
@@ -941,13 +2994,780 @@ pub enum BattleError {
     UnclaimedPrizes,
     #[msg("There are unclaimed refunds")]
     UnclaimedRefunds,
+    #[msg("Invalid bet account")]
+    InvalidBetAccount,
+    #[msg("Bet is not settled")]
+    BetNotSettled,
+    #[msg("There are open bet accounts")]
+    OpenBetAccounts,
+    #[msg("Fee basis points exceed 100%")]
+    InvalidFeeBps,
+    #[msg("Minimum bet exceeds maximum bet")]
+    InvalidBetLimits,
+    #[msg("Bet amount is too large")]
+    BetTooLarge,
+    #[msg("Invalid treasury account")]
+    InvalidTreasury,
+    #[msg("Bump is not the canonical bump")]
+    InvalidBump,
+    #[msg("Role already has the maximum number of members")]
+    RoleFull,
+    #[msg("Key already holds this role")]
+    RoleMemberExists,
+    #[msg("Key does not hold this role")]
+    RoleMemberNotFound,
+    #[msg("Withdrawal would leave the house below its rent-exempt minimum")]
+    WithdrawalExceedsFreeBalance,
+    #[msg("Token accounts are required for token matches")]
+    MissingTokenAccounts,
+    #[msg("Mint does not match the match's bet mint")]
+    InvalidBetMint,
+    #[msg("Schedule must run opens < closes <= expected end <= resolution deadline")]
+    InvalidSchedule,
+    #[msg("Betting has not opened yet")]
+    BettingNotOpen,
+    #[msg("Betting has closed")]
+    BettingClosed,
+    #[msg("Betting is still open")]
+    BettingStillOpen,
+    #[msg("Resolution deadline has not been reached")]
+    ResolutionDeadlineNotReached,
+    #[msg("Resolution deadline has passed")]
+    ResolutionDeadlinePassed,
+    #[msg("A match needs between 2 and 16 fighters")]
+    InvalidFighterCount,
+    #[msg("Fighter names must be unique")]
+    DuplicateFighter,
+    #[msg("Payout split must have 1-5 non-zero places, fewer than the fighters, summing to 100%")]
+    InvalidPayoutSplit,
+    #[msg("Payout split cannot change once bets are placed")]
+    PayoutSplitLocked,
+    #[msg("Placements must name one distinct fighter per payout place")]
+    InvalidPlacements,
+    #[msg("A bracket needs 2, 4, 8 or 16 fighters")]
+    InvalidBracketSize,
+    #[msg("Invalid bracket slot")]
+    InvalidBracketSlot,
+    #[msg("Bracket slot already has a match")]
+    BracketSlotTaken,
+    #[msg("Not every fighter in this match is decided yet")]
+    FightersNotDecided,
+    #[msg("Invalid tournament account")]
+    InvalidTournament,
+    #[msg("Tournament accounts are required for tournament matches")]
+    MissingTournamentAccounts,
+    #[msg("Next match does not belong to the next bracket slot")]
+    InvalidNextMatch,
+    #[msg("Tournament matches must end with a winner")]
+    TournamentMatchNeedsWinner,
+    #[msg("Tournament winner pools are settled when the final ends")]
+    TournamentPoolSettlesItself,
+    #[msg("Tournament has no champion yet")]
+    TournamentNotFinished,
+    #[msg("Decrease must be less than the bet; cancel the bet to withdraw all of it")]
+    InvalidDecreaseAmount,
+    #[msg("This match does not allow betting on more than one fighter")]
+    HedgingNotAllowed,
+    #[msg("A hedge bet is required only when betting on a different fighter than your first bet")]
+    InvalidHedgeBet,
+    #[msg("Bet would take this wallet over the match's per-wallet limit")]
+    WalletLimitExceeded,
+    #[msg("Bet would take this fighter's pool over the match's limit")]
+    FighterPoolLimitExceeded,
+    #[msg("Cancel hedge bets before the main bet")]
+    HedgeBetsOpen,
+    #[msg("The main bet must be passed when, and only when, withdrawing from a hedge bet")]
+    MainBetRequired,
+    #[msg("The match escrow is required for SOL matches")]
+    MissingEscrow,
+    #[msg("Strict payout could not pay every targeted bettor")]
+    PayoutShortfall,
+    #[msg("Bets must be paged in index order from the settled cursor")]
+    BetOutOfOrder,
+    #[msg("Bet was cancelled")]
+    BetCancelled,
+    #[msg("House wallet already uses the current layout")]
+    AlreadyMigrated,
+    #[msg("Account is not in the legacy layout")]
+    NotLegacyAccount,
+    #[msg("Tournament matches always pay a single winner")]
+    TournamentPayoutFixed,
+    #[msg("Match is not part of a tournament bracket")]
+    NotBracketMatch,
+    #[msg("A fighter has already advanced from this bracket slot")]
+    BracketSlotAdvanced,
 }
 
 // Helper functions
+const MAX_BPS: u16 = 10_000;
+
 fn is_authorized(authority: Pubkey, house_wallet: &HouseWallet) -> bool {
     authority == house_wallet.authority
 }
 
+fn has_role(key: Pubkey, house_wallet: &HouseWallet, role: Role) -> bool {
+    is_authorized(key, house_wallet) || house_wallet.role_members(role).contains(&key)
+}
+
+fn add_role_member(house_wallet: &mut HouseWallet, role: Role, member: Pubkey) -> Result<()> {
+    let members = house_wallet.role_members_mut(role);
+    require!(!members.contains(&member), BattleError::RoleMemberExists);
+    require!(members.len() < HouseWallet::MAX_ROLE_MEMBERS, BattleError::RoleFull);
+    members.push(member);
+    
+    emit!(RoleChanged {
+        role,
+        member,
+        granted: true,
+    });
+    msg!("Role granted - Role: {:?}, Member: {}", role, member);
+    Ok(())
+}
+
+fn remove_role_member(house_wallet: &mut HouseWallet, role: Role, member: Pubkey) -> Result<()> {
+    let members = house_wallet.role_members_mut(role);
+    let index = members.iter().position(|key| *key == member)
+        .ok_or(BattleError::RoleMemberNotFound)?;
+    members.remove(index);
+    
+    emit!(RoleChanged {
+        role,
+        member,
+        granted: false,
+    });
+    msg!("Role revoked - Role: {:?}, Member: {}", role, member);
+    Ok(())
+}
+
+fn validate_fighters(fighters: &[String]) -> Result<()> {
+    require!(
+        (2..=MatchAccount::MAX_FIGHTERS).contains(&fighters.len()),
+        BattleError::InvalidFighterCount
+    );
+    for (index, fighter) in fighters.iter().enumerate() {
+        require!(!fighter.is_empty() && fighter.len() <= 10, BattleError::InvalidFighterLength);
+        require!(!fighters[..index].contains(fighter), BattleError::DuplicateFighter);
+    }
+    Ok(())
+}
+
+fn init_match_account(
+    match_account: &mut MatchAccount,
+    house_wallet: &HouseWallet,
+    match_id: &str,
+    fighters: &[String],
+    schedule: &MatchSchedule,
+) -> Result<()> {
+    require!(
+        schedule.betting_opens_at < schedule.betting_closes_at &&
+        schedule.betting_closes_at <= schedule.expected_end_at &&
+        schedule.expected_end_at <= schedule.resolution_deadline,
+        BattleError::InvalidSchedule
+    );
+    
+    match_account.match_id = match_id.to_string();
+    match_account.fighters = fighters.iter()
+        .map(|name| FighterPool {
+            name: name.clone(),
+            total_bets: 0,
+            bet_count: 0,
+        })
+        .collect();
+    match_account.status = MatchStatus::Preparation;
+    match_account.winner = None;
+    match_account.prize_pool = 0;
+    // Winner takes the whole prize pool unless an operator sets a podium split
+    match_account.payout_split = vec![MAX_BPS];
+    match_account.placements = Vec::new();
+    // Snapshot the house terms so later config changes don't affect this match
+    match_account.fee_bps = house_wallet.fee_bps;
+    match_account.max_fee = house_wallet.max_fee;
+    match_account.min_bet = house_wallet.min_bet;
+    match_account.max_bet = house_wallet.max_bet;
+    match_account.max_wallet_total = u64::MAX;
+    match_account.max_fighter_pool = u64::MAX;
+    match_account.draw_handling = house_wallet.draw_handling;
+    match_account.cancel_fee_bps = house_wallet.cancel_fee_bps;
+    match_account.hedging_policy = house_wallet.hedging_policy;
+    match_account.treasury = house_wallet.treasury;
+    match_account.claimed_count = 0;
+    match_account.paid_out = 0;
+    match_account.closed_bet_count = 0;
+    match_account.next_bet_index = 0;
+    match_account.settled_cursor = 0;
+    match_account.bet_mint = None;
+    match_account.vault_bump = 0;
+    match_account.betting_opens_at = schedule.betting_opens_at;
+    match_account.betting_closes_at = schedule.betting_closes_at;
+    match_account.expected_end_at = schedule.expected_end_at;
+    match_account.resolution_deadline = schedule.resolution_deadline;
+    match_account.tournament = None;
+    match_account.bracket_slot = None;
+    Ok(())
+}
+
+fn emit_remainder_paid(match_account: &Account<MatchAccount>, recipient: Pubkey, amount: u64) {
+    emit!(RemainderPaid {
+        match_account: match_account.key(),
+        match_id: match_account.match_id.clone(),
+        recipient,
+        amount,
+        paid_out: match_account.paid_out,
+    });
+    msg!("Rounding remainder paid - Recipient: {}, Amount: {}", recipient, amount);
+}
+
+fn emit_match_created(match_account: &Account<MatchAccount>) {
+    emit!(MatchCreated {
+        match_account: match_account.key(),
+        match_id: match_account.match_id.clone(),
+        fighters: match_account.fighters.iter().map(|pool| pool.name.clone()).collect(),
+        bet_mint: match_account.bet_mint,
+        tournament: match_account.tournament,
+        betting_opens_at: match_account.betting_opens_at,
+        betting_closes_at: match_account.betting_closes_at,
+        resolution_deadline: match_account.resolution_deadline,
+    });
+}
+
+fn set_status(match_account: &mut Account<MatchAccount>, status: MatchStatus) {
+    emit!(StatusChanged {
+        match_account: match_account.key(),
+        match_id: match_account.match_id.clone(),
+        previous_status: match_account.status.clone(),
+        status: status.clone(),
+    });
+    match_account.status = status;
+}
+
+fn token_accounts<'a, 'info>(
+    bet_mint: &'a Option<InterfaceAccount<'info, Mint>>,
+    vault: &'a Option<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &'a Option<Interface<'info, TokenInterface>>,
+) -> Result<(
+    &'a InterfaceAccount<'info, Mint>,
+    &'a InterfaceAccount<'info, TokenAccount>,
+    &'a Interface<'info, TokenInterface>,
+)> {
+    match (bet_mint, vault, token_program) {
+        (Some(bet_mint), Some(vault), Some(token_program)) => Ok((bet_mint, vault, token_program)),
+        _ => err!(BattleError::MissingTokenAccounts),
+    }
+}
+
+fn transfer_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    bet_mint: &InterfaceAccount<'info, Mint>,
+    match_account: AccountInfo<'info>,
+    match_seeds: &[&[u8]],
+    destination: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault.to_account_info(),
+                mint: bet_mint.to_account_info(),
+                to: destination,
+                authority: match_account,
+            },
+            &[match_seeds],
+        ),
+        amount,
+        bet_mint.decimals,
+    )
+}
+
+/// Pays `amount` to a bettor from the match's token vault or escrow and records the payout. The
+/// match's last payout also takes the rounding remainder the floored shares left behind; returns
+/// `(payout, remainder)` with the remainder included in `payout`.
+fn pay_bettor<'info>(
+    match_account: &mut Account<'info, MatchAccount>,
+    token: Option<(
+        &InterfaceAccount<'info, Mint>,
+        &InterfaceAccount<'info, TokenAccount>,
+        &Interface<'info, TokenInterface>,
+    )>,
+    escrow: &Option<Account<'info, Escrow>>,
+    bettor: Pubkey,
+    destination: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<(u64, u64)> {
+    let remainder = match_account.payout_remainder(amount);
+    let payout = amount.checked_add(remainder).ok_or(BattleError::Overflow)?;
+    
+    if let Some((bet_mint, vault, token_program)) = token {
+        check_token_destination(destination, bettor, bet_mint.key())?;
+        require!(vault.amount >= payout, BattleError::InsufficientFunds);
+        let match_bump = [match_account.bump];
+        let match_seeds: &[&[u8]] = &[b"match", match_account.match_id.as_bytes(), &match_bump];
+        transfer_from_vault(
+            token_program,
+            vault,
+            bet_mint,
+            match_account.to_account_info(),
+            match_seeds,
+            destination.clone(),
+            payout,
+        )?;
+    } else {
+        require_keys_eq!(destination.key(), bettor, BattleError::InvalidBettorAccount);
+        transfer_from_escrow(&escrow_info(escrow)?, destination, payout)?;
+    }
+    
+    match_account.record_payout(payout)?;
+    if remainder > 0 {
+        emit_remainder_paid(match_account, bettor, remainder);
+    }
+    Ok((payout, remainder))
+}
+
+fn escrow_info<'info>(escrow: &Option<Account<'info, Escrow>>) -> Result<AccountInfo<'info>> {
+    escrow.as_ref()
+        .map(|escrow| escrow.to_account_info())
+        .ok_or(error!(BattleError::MissingEscrow))
+}
+
+/// Lamports in a match escrow above its rent-exempt minimum.
+fn escrow_balance(escrow: &AccountInfo) -> Result<u64> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(escrow.data_len());
+    Ok(escrow.lamports().saturating_sub(rent_exempt_minimum))
+}
+
+/// Error for a strict payout that cannot pay `bettor`, attributed to the bettor's key.
+fn payout_shortfall(bettor: Pubkey, amount: u64) -> Error {
+    msg!("Strict payout failed - Bettor: {}, Amount: {}", bettor, amount);
+    error!(BattleError::PayoutShortfall).with_account_name(bettor.to_string())
+}
+
+fn transfer_from_escrow(escrow: &AccountInfo, destination: &AccountInfo, amount: u64) -> Result<()> {
+    require!(escrow_balance(escrow)? >= amount, BattleError::InsufficientFunds);
+    **escrow.try_borrow_mut_lamports()? -= amount;
+    **destination.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+fn check_token_destination(token_account_info: &AccountInfo, owner: Pubkey, mint: Pubkey) -> Result<()> {
+    let token_account = TokenAccount::try_deserialize(&mut &token_account_info.try_borrow_data()?[..])?;
+    require_keys_eq!(token_account.owner, owner, BattleError::InvalidBettorAccount);
+    require_keys_eq!(token_account.mint, mint, BattleError::InvalidBetMint);
+    Ok(())
+}
+
+fn load_bet(bet_info: &AccountInfo, match_account: Pubkey) -> Result<Bet> {
+    require_keys_eq!(*bet_info.owner, crate::ID, BattleError::InvalidBetAccount);
+    let bet = Bet::try_deserialize(&mut &bet_info.try_borrow_data()?[..])?;
+    require_keys_eq!(bet.match_account, match_account, BattleError::InvalidBetAccount);
+    Ok(bet)
+}
+
+fn store_bet(bet_info: &AccountInfo, bet: &Bet) -> Result<()> {
+    bet.try_serialize(&mut &mut bet_info.try_borrow_mut_data()?[..])
+}
+
+/// Pays one page of bets passed as (bet, bettor) pairs what `owed` returns for each, skipping
+/// bets it returns None for. Returns the number of bets paid, their total, and whether any bet
+/// was left unpaid for lack of funds.
+fn settle_bets<'info>(
+    match_account: &mut Account<'info, MatchAccount>,
+    token: Option<(
+        &InterfaceAccount<'info, Mint>,
+        &InterfaceAccount<'info, TokenAccount>,
+        &Interface<'info, TokenInterface>,
+    )>,
+    escrow: &Option<Account<'info, Escrow>>,
+    remaining_accounts: &[AccountInfo<'info>],
+    strict: bool,
+    mut owed: impl FnMut(&MatchAccount, &Bet) -> Result<Option<u64>>,
+) -> Result<(u32, u64, bool)> {
+    let match_key = match_account.key();
+    let mut vault_balance = match token {
+        Some((_, vault, _)) => vault.amount,
+        None => escrow_balance(&escrow_info(escrow)?)?,
+    };
+    let bet_pairs = remaining_accounts.chunks_exact(2);
+    require!(bet_pairs.remainder().is_empty(), BattleError::InvalidRemainingAccounts);
+    let mut next_index = match_account.settled_cursor;
+    let mut paid_count = 0;
+    let mut total_paid = 0;
+    let mut has_unpaid_bets = false;
+    
+    for accounts in bet_pairs {
+        let bet_info = &accounts[0];
+        let bettor_account = &accounts[1];
+        
+        let mut bet = load_bet(bet_info, match_key)?;
+        // Pages walk bets in index order from the settled cursor, which only moves
+        // through contiguous indices; bets past a gap wait for the page that fills it
+        require!(bet.index >= next_index, BattleError::BetOutOfOrder);
+        next_index = bet.index + 1;
+        let at_cursor = bet.index == match_account.settled_cursor;
+        if at_cursor {
+            match_account.settled_cursor = next_index;
+        }
+        if bet.claimed || bet.amount == 0 {
+            continue;
+        }
+        let Some(amount) = owed(match_account, &bet)? else {
+            continue;
+        };
+        
+        let needed = amount.saturating_add(match_account.payout_remainder(amount));
+        if vault_balance < needed {
+            // Strict mode fails the whole batch instead of paying only some bettors
+            if strict {
+                return Err(payout_shortfall(bet.bettor, needed));
+            }
+            // Later bets in the page may still be paid, but the cursor stays here
+            if at_cursor {
+                match_account.settled_cursor = bet.index;
+            }
+            msg!(
+                "Insufficient funds in match vault to pay bettor: {}, Amount: {}",
+                bet.bettor,
+                needed
+            );
+            has_unpaid_bets = true;
+            continue;
+        }
+        
+        let (payout, _) = pay_bettor(match_account, token, escrow, bet.bettor, bettor_account, amount)?;
+        vault_balance -= payout;
+        
+        // Mark as claimed only if transfer was successful
+        bet.claimed = true;
+        store_bet(bet_info, &bet)?;
+        total_paid += payout;
+        paid_count += 1;
+        
+        // A completed match pays prizes, the stake plus a share; every other payout is a refund
+        if match_account.status == MatchStatus::Completed {
+            let prize_share = amount.saturating_sub(bet.amount);
+            emit!(PrizePaid {
+                match_account: match_key,
+                match_id: match_account.match_id.clone(),
+                bettor: bet.bettor,
+                amount,
+                prize_share,
+            });
+            msg!(
+                "Prize sent - Bettor: {}, Amount: {}, Prize Share: {}",
+                bet.bettor,
+                bet.amount,
+                prize_share
+            );
+        } else {
+            emit!(RefundPaid {
+                match_account: match_key,
+                match_id: match_account.match_id.clone(),
+                bettor: bet.bettor,
+                amount,
+            });
+            msg!(
+                "Refund sent - Bettor: {}, Amount: {}",
+                bet.bettor,
+                amount
+            );
+        }
+    }
+    
+    msg!("Settled cursor at bet index {}", match_account.settled_cursor);
+    Ok((paid_count, total_paid, has_unpaid_bets))
+}
+
+/// Settles a match in Battle: records the outcome and sends the fee to the treasury.
+fn resolve_match(accounts: &mut EndMatch, match_id: &str, result: MatchResult) -> Result<()> {
+    let match_account = &mut accounts.match_account;
+    require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+    require!(match_account.status == MatchStatus::Battle, BattleError::MatchNotInBattle);
+    require!(match_account.winner.is_none(), BattleError::MatchAlreadyEnded);
+    require!(
+        Clock::get()?.unix_timestamp < match_account.resolution_deadline,
+        BattleError::ResolutionDeadlinePassed
+    );
+    require!(match_account.fighters_decided(), BattleError::FightersNotDecided);
+    
+    let (status, placements, pool, fee) = match result {
+        MatchResult::Winner(winner) => rank_placements(match_account, vec![winner])?,
+        MatchResult::Placements(placed) => rank_placements(match_account, placed)?,
+        MatchResult::Draw | MatchResult::NoContest => {
+            let status = if matches!(result, MatchResult::Draw) {
+                MatchStatus::Draw
+            } else {
+                MatchStatus::NoContest
+            };
+            
+            // Every stake is returned, less the usual fee if the match says so
+            let total_bets = match_account.total_pool();
+            let fee = match match_account.draw_handling {
+                DrawHandling::FullRefund => 0,
+                DrawHandling::RefundMinusFee => calculate_fee(total_bets, match_account.fee_bps, match_account.max_fee)?,
+            };
+            (status, Vec::new(), total_bets, fee)
+        },
+    };
+    
+    // If nobody backed a placed fighter, or nobody bet against them, refund all bets
+    if status == MatchStatus::Refund {
+        set_status(match_account, MatchStatus::Refund);
+        emit!(MatchEnded {
+            match_account: match_account.key(),
+            match_id: match_id.to_string(),
+            status,
+            winner: None,
+            placements: Vec::new(),
+            prize_pool: 0,
+            fee: 0,
+        });
+        msg!("Match ended in refund due to no bets on the placed fighters or against them");
+        return Ok(());
+    }
+    
+    // Transfer fee to treasury
+    if match_account.bet_mint.is_some() {
+        let (bet_mint, vault, token_program) = token_accounts(
+            &accounts.bet_mint,
+            &accounts.vault,
+            &accounts.token_program,
+        )?;
+        let treasury_token_account = accounts.treasury_token_account.as_ref()
+            .ok_or(BattleError::MissingTokenAccounts)?
+            .to_account_info();
+        check_token_destination(&treasury_token_account, match_account.treasury, bet_mint.key())?;
+        let match_bump = [match_account.bump];
+        let match_seeds: &[&[u8]] = &[b"match", match_id.as_bytes(), &match_bump];
+        
+        transfer_from_vault(
+            token_program,
+            vault,
+            bet_mint,
+            match_account.to_account_info(),
+            match_seeds,
+            treasury_token_account,
+            fee,
+        )?;
+    } else {
+        transfer_from_escrow(&escrow_info(&accounts.escrow)?, &accounts.treasury, fee)?;
+    }
+
+    match_account.winner = placements.first().map(|placement| placement.fighter.clone());
+    match_account.placements = placements;
+    match_account.prize_pool = pool - fee;
+    set_status(match_account, status);
+    
+    emit!(MatchEnded {
+        match_account: match_account.key(),
+        match_id: match_id.to_string(),
+        status: match_account.status.clone(),
+        winner: match_account.winner.clone(),
+        placements: match_account.placements.clone(),
+        prize_pool: match_account.prize_pool,
+        fee,
+    });
+
+    match &match_account.winner {
+        Some(winner) => msg!(
+            "Match ended - ID: {}, Winner: {}, Prize Pool: {}, Fee transferred to treasury: {}",
+            match_id,
+            winner,
+            match_account.prize_pool,
+            fee
+        ),
+        None => msg!(
+            "Match ended without a winner - ID: {}, Result: {:?}, Refund Pool: {}, Fee transferred to treasury: {}",
+            match_id,
+            match_account.status,
+            match_account.prize_pool,
+            fee
+        ),
+    }
+
+    Ok(())
+}
+
+/// Returns part of a bet before betting closes, less the match's cancellation fee.
+fn withdraw_stake(accounts: &mut WithdrawBet, match_id: &str, amount: u64) -> Result<()> {
+    require!(!accounts.house_wallet.paused, BattleError::ProgramPaused);
+    
+    let match_account = &mut accounts.match_account;
+    require!(match_account.match_id == match_id, BattleError::InvalidMatchId);
+    // A refunded stake has already left the pool; withdrawing it would pay it out twice
+    require!(!accounts.bet.claimed, BattleError::AlreadyClaimed);
+    require!(match_account.status == MatchStatus::Preparation, BattleError::MatchNotInPreparation);
+    require!(Clock::get()?.unix_timestamp < match_account.betting_closes_at, BattleError::BettingClosed);
+    
+    let bet = &mut accounts.bet;
+    require!(bet.hedge == accounts.main_bet.is_some(), BattleError::MainBetRequired);
+    let fee = calculate_fee(amount, match_account.cancel_fee_bps, match_account.max_fee)?;
+    let refund = amount - fee;
+    
+    if match_account.bet_mint.is_some() {
+        let (bet_mint, vault, token_program) = token_accounts(
+            &accounts.bet_mint,
+            &accounts.vault,
+            &accounts.token_program,
+        )?;
+        let bettor_token_account = accounts.bettor_token_account.as_ref()
+            .ok_or(BattleError::MissingTokenAccounts)?
+            .to_account_info();
+        check_token_destination(&bettor_token_account, bet.bettor, bet_mint.key())?;
+        let match_bump = [match_account.bump];
+        let match_seeds: &[&[u8]] = &[b"match", match_id.as_bytes(), &match_bump];
+        
+        transfer_from_vault(
+            token_program,
+            vault,
+            bet_mint,
+            match_account.to_account_info(),
+            match_seeds,
+            bettor_token_account,
+            refund,
+        )?;
+        if fee > 0 {
+            let treasury_token_account = accounts.treasury_token_account.as_ref()
+                .ok_or(BattleError::MissingTokenAccounts)?
+                .to_account_info();
+            check_token_destination(&treasury_token_account, match_account.treasury, bet_mint.key())?;
+            transfer_from_vault(
+                token_program,
+                vault,
+                bet_mint,
+                match_account.to_account_info(),
+                match_seeds,
+                treasury_token_account,
+                fee,
+            )?;
+        }
+    } else {
+        let escrow = escrow_info(&accounts.escrow)?;
+        transfer_from_escrow(&escrow, &accounts.bettor.to_account_info(), refund)?;
+        transfer_from_escrow(&escrow, &accounts.treasury, fee)?;
+    }
+    
+    bet.amount -= amount;
+    let main_bet = accounts.main_bet.as_deref_mut().unwrap_or(bet);
+    main_bet.wallet_total -= amount;
+    let bet = &accounts.bet;
+    let pool = match_account.fighter_mut(&bet.fighter).ok_or(BattleError::InvalidFighter)?;
+    pool.total_bets -= amount;
+    
+    emit!(BetWithdrawn {
+        match_account: match_account.key(),
+        match_id: match_id.to_string(),
+        bettor: bet.bettor,
+        fighter: bet.fighter.clone(),
+        amount,
+        fee,
+        remaining: bet.amount,
+    });
+    
+    msg!(
+        "Bet withdrawn - Match: {}, Bettor: {}, Amount: {}, Fee: {}, Remaining: {}",
+        match_id,
+        bet.bettor,
+        amount,
+        fee,
+        bet.amount
+    );
+    Ok(())
+}
+
+/// Moves a bracket match's winner into the next round, or crowns the champion after the final.
+fn advance_bracket(
+    tournament: &mut Account<Tournament>,
+    next_match: Option<&mut Account<MatchAccount>>,
+    slot: usize,
+    winner: String,
+) -> Result<()> {
+    match Tournament::next_slot(slot) {
+        Some((next_slot, position)) => {
+            let next_match = next_match.ok_or(BattleError::MissingTournamentAccounts)?;
+            require_keys_eq!(next_match.key(), tournament.matches[next_slot], BattleError::InvalidNextMatch);
+            require!(next_match.fighters[position].name.is_empty(), BattleError::BracketSlotAdvanced);
+            next_match.fighters[position].name = winner.clone();
+            
+            emit!(BracketAdvanced {
+                tournament: tournament.key(),
+                tournament_id: tournament.tournament_id.clone(),
+                slot: slot as u8,
+                fighter: winner.clone(),
+                next_match: Some(next_match.key()),
+            });
+            msg!(
+                "Bracket advanced - Tournament: {}, Fighter: {}, Next match: {}",
+                tournament.tournament_id,
+                winner,
+                next_match.match_id
+            );
+        },
+        None => {
+            require!(tournament.champion.is_none(), BattleError::BracketSlotAdvanced);
+            tournament.champion = Some(winner.clone());
+            emit!(BracketAdvanced {
+                tournament: tournament.key(),
+                tournament_id: tournament.tournament_id.clone(),
+                slot: slot as u8,
+                fighter: winner.clone(),
+                next_match: None,
+            });
+            msg!("Tournament won - Tournament: {}, Champion: {}", tournament.tournament_id, winner);
+        },
+    }
+    Ok(())
+}
+
+/// Splits the losing pools across the podium, returning the status, placements,
+/// losing pool and fee. Places nobody backed pass their share to the others.
+fn rank_placements(match_account: &MatchAccount, placed: Vec<String>) -> Result<(MatchStatus, Vec<Placement>, u64, u64)> {
+    require!(placed.len() == match_account.payout_split.len(), BattleError::InvalidPlacements);
+    
+    let mut placed_bets = Vec::with_capacity(placed.len());
+    for (index, fighter) in placed.iter().enumerate() {
+        require!(!placed[..index].contains(fighter), BattleError::InvalidPlacements);
+        let pool = match_account.fighter(fighter).ok_or(BattleError::InvalidWinner)?;
+        placed_bets.push(pool.total_bets);
+    }
+    
+    // Every pool off the podium funds the podium
+    let total_placed_bets: u64 = placed_bets.iter().sum();
+    let total_losing_bets = match_account.total_pool() - total_placed_bets;
+    if total_placed_bets == 0 || total_losing_bets == 0 {
+        return Ok((MatchStatus::Refund, Vec::new(), 0, 0));
+    }
+    
+    // Calculate fee on losing bets using the match's fee schedule
+    let fee = calculate_fee(total_losing_bets, match_account.fee_bps, match_account.max_fee)?;
+    let prize_pool = total_losing_bets - fee;
+    
+    let weights: Vec<u64> = match_account.payout_split.iter()
+        .zip(&placed_bets)
+        .map(|(bps, total_bets)| if *total_bets > 0 { *bps as u64 } else { 0 })
+        .collect();
+    let total_weight: u64 = weights.iter().sum();
+    
+    let mut placements = Vec::with_capacity(placed.len());
+    for (fighter, weight) in placed.into_iter().zip(weights) {
+        placements.push(Placement {
+            fighter,
+            prize_pool: calculate_prize_share(weight, prize_pool, total_weight)?,
+        });
+    }
+    
+    Ok((MatchStatus::Completed, placements, total_losing_bets, fee))
+}
+
+fn calculate_fee(amount: u64, fee_bps: u16, max_fee: u64) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(BattleError::Overflow)?
+        .checked_div(MAX_BPS as u128)
+        .ok_or(BattleError::Overflow)?;
+        
+    Ok((fee as u64).min(max_fee))
+}
+
 fn calculate_prize_share(bet_amount: u64, prize_pool: u64, total_winning_bets: u64) -> Result<u64> {
     if total_winning_bets == 0 {
         return Ok(0);
@@ -961,3 +3781,184 @@ fn calculate_prize_share(bet_amount: u64, prize_pool: u64, total_winning_bets: u
         
     Ok(prize_share as u64)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_match(pools: &[(&str, u64, u32)], payout_split: Vec<u16>, fee_bps: u16) -> MatchAccount {
+        MatchAccount {
+            match_id: "MATCH_001".to_string(),
+            fighters: pools.iter()
+                .map(|(name, total_bets, bet_count)| FighterPool {
+                    name: name.to_string(),
+                    total_bets: *total_bets,
+                    bet_count: *bet_count,
+                })
+                .collect(),
+            status: MatchStatus::Battle,
+            winner: None,
+            prize_pool: 0,
+            payout_split,
+            placements: Vec::new(),
+            fee_bps,
+            max_fee: u64::MAX,
+            min_bet: 0,
+            max_bet: 0,
+            max_wallet_total: 0,
+            max_fighter_pool: 0,
+            treasury: Pubkey::default(),
+            claimed_count: 0,
+            paid_out: 0,
+            closed_bet_count: 0,
+            next_bet_index: 0,
+            settled_cursor: 0,
+            bet_mint: None,
+            vault_bump: 0,
+            betting_opens_at: 0,
+            betting_closes_at: 0,
+            expected_end_at: 0,
+            resolution_deadline: 0,
+            draw_handling: DrawHandling::FullRefund,
+            cancel_fee_bps: 0,
+            hedging_policy: HedgingPolicy::SameFighterOnly,
+            tournament: None,
+            bracket_slot: None,
+            bump: 0,
+        }
+    }
+
+    fn placed(fighters: &[&str]) -> Vec<String> {
+        fighters.iter().map(|fighter| fighter.to_string()).collect()
+    }
+
+    fn settle(match_account: &mut MatchAccount, fighters: &[&str]) {
+        let (status, placements, total_losing_bets, fee) = rank_placements(match_account, placed(fighters)).unwrap();
+        match_account.status = status;
+        match_account.placements = placements;
+        match_account.prize_pool = total_losing_bets - fee;
+    }
+
+    #[test]
+    fn calculate_fee_takes_bps_of_amount() {
+        assert_eq!(calculate_fee(1_000, 500, u64::MAX).unwrap(), 50);
+        assert_eq!(calculate_fee(1_999, 1, u64::MAX).unwrap(), 0);
+        assert_eq!(calculate_fee(1_000, 0, u64::MAX).unwrap(), 0);
+    }
+
+    #[test]
+    fn calculate_fee_is_capped_by_max_fee() {
+        assert_eq!(calculate_fee(1_000_000, 500, 10_000).unwrap(), 10_000);
+        assert_eq!(calculate_fee(u64::MAX, MAX_BPS, u64::MAX).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn rank_placements_pays_losing_pools_less_fee_to_winner() {
+        let match_account = test_match(&[("DOGE", 300, 3), ("PEPE", 700, 2)], vec![MAX_BPS], 500);
+        let (status, placements, total_losing_bets, fee) = rank_placements(&match_account, placed(&["DOGE"])).unwrap();
+        
+        assert_eq!(status, MatchStatus::Completed);
+        assert_eq!(total_losing_bets, 700);
+        assert_eq!(fee, 35);
+        assert_eq!(placements.len(), 1);
+        assert_eq!(placements[0].fighter, "DOGE");
+        assert_eq!(placements[0].prize_pool, 665);
+    }
+
+    #[test]
+    fn rank_placements_splits_prize_pool_across_podium() {
+        let match_account = test_match(&[("DOGE", 100, 1), ("PEPE", 100, 1), ("SHIB", 1_000, 4)], vec![6_000, 4_000], 0);
+        let (_, placements, _, _) = rank_placements(&match_account, placed(&["DOGE", "PEPE"])).unwrap();
+        
+        assert_eq!(placements[0].prize_pool, 600);
+        assert_eq!(placements[1].prize_pool, 400);
+    }
+
+    #[test]
+    fn rank_placements_moves_empty_podium_spots_to_the_rest() {
+        let match_account = test_match(&[("DOGE", 100, 1), ("PEPE", 0, 0), ("SHIB", 1_000, 4)], vec![6_000, 4_000], 0);
+        let (_, placements, _, _) = rank_placements(&match_account, placed(&["DOGE", "PEPE"])).unwrap();
+        
+        assert_eq!(placements[0].prize_pool, 1_000);
+        assert_eq!(placements[1].prize_pool, 0);
+    }
+
+    #[test]
+    fn rank_placements_refunds_when_no_one_won_or_lost() {
+        let no_winners = test_match(&[("DOGE", 0, 0), ("PEPE", 700, 2)], vec![MAX_BPS], 500);
+        let (status, placements, _, fee) = rank_placements(&no_winners, placed(&["DOGE"])).unwrap();
+        assert_eq!(status, MatchStatus::Refund);
+        assert!(placements.is_empty());
+        assert_eq!(fee, 0);
+        
+        let no_losers = test_match(&[("DOGE", 300, 3), ("PEPE", 0, 0)], vec![MAX_BPS], 500);
+        let (status, _, _, _) = rank_placements(&no_losers, placed(&["DOGE"])).unwrap();
+        assert_eq!(status, MatchStatus::Refund);
+    }
+
+    #[test]
+    fn rank_placements_rejects_invalid_podiums() {
+        let match_account = test_match(&[("DOGE", 100, 1), ("PEPE", 100, 1), ("SHIB", 1_000, 4)], vec![6_000, 4_000], 0);
+        
+        assert!(rank_placements(&match_account, placed(&["DOGE"])).is_err());
+        assert!(rank_placements(&match_account, placed(&["DOGE", "DOGE"])).is_err());
+        assert!(rank_placements(&match_account, placed(&["DOGE", "BONK"])).is_err());
+    }
+
+    #[test]
+    fn payout_remainder_goes_to_last_claimer_only() {
+        let mut match_account = test_match(&[("DOGE", 100, 1), ("PEPE", 100, 1), ("SHIB", 999, 4)], vec![5_000, 5_000], 0);
+        settle(&mut match_account, &["DOGE", "PEPE"]);
+        
+        // 999 split evenly leaves one lamport of dust between the two tiers
+        assert_eq!(match_account.settlement_total(), 1_199);
+        let first = 100 + match_account.prize_share(100, "DOGE").unwrap();
+        assert_eq!(first, 599);
+        assert_eq!(match_account.payout_remainder(first), 0);
+        match_account.record_payout(first).unwrap();
+        
+        let last = 100 + match_account.prize_share(100, "PEPE").unwrap();
+        assert_eq!(match_account.payout_remainder(last), 1);
+        match_account.record_payout(last + 1).unwrap();
+        assert_eq!(match_account.paid_out, match_account.settlement_total());
+    }
+
+    #[test]
+    fn payout_remainder_covers_refunds() {
+        let mut match_account = test_match(&[("DOGE", 100, 1), ("PEPE", 50, 2)], vec![MAX_BPS], 0);
+        match_account.status = MatchStatus::Refund;
+        
+        assert_eq!(match_account.settlement_total(), 150);
+        assert_eq!(match_account.payout_remainder(100), 0);
+        match_account.record_payout(100).unwrap();
+        assert_eq!(match_account.payout_remainder(25), 0);
+        match_account.record_payout(25).unwrap();
+        assert_eq!(match_account.payout_remainder(20), 5);
+    }
+
+    #[test]
+    fn next_slot_feeds_winners_toward_the_final() {
+        assert_eq!(Tournament::next_slot(0), None);
+        assert_eq!(Tournament::next_slot(1), Some((0, 0)));
+        assert_eq!(Tournament::next_slot(2), Some((0, 1)));
+        assert_eq!(Tournament::next_slot(5), Some((2, 0)));
+        assert_eq!(Tournament::next_slot(6), Some((2, 1)));
+    }
+
+    #[test]
+    fn slot_fighters_seeds_only_the_first_round() {
+        let tournament = Tournament {
+            tournament_id: "CUP".to_string(),
+            fighters: placed(&["A", "B", "C", "D", "E", "F", "G", "H"]),
+            matches: Vec::new(),
+            winner_pool: Pubkey::default(),
+            champion: None,
+            bump: 0,
+        };
+        
+        assert_eq!(tournament.slot_fighters(3), placed(&["A", "B"]));
+        assert_eq!(tournament.slot_fighters(6), placed(&["G", "H"]));
+        assert_eq!(tournament.slot_fighters(0), vec![String::new(), String::new()]);
+        assert_eq!(tournament.slot_fighters(2), vec![String::new(), String::new()]);
+    }
+}
@@ -0,0 +1,127 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID of the refunded bracket match',
+    type: 'string',
+    demandOption: true
+  })
+  .option('winner', {
+    alias: 'w',
+    description: 'Fighter that goes through to the next round',
+    type: 'string',
+    demandOption: true
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'arena-authority.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDAs for the match and house wallet
+    const [matchAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(argv.matchid)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    const matchData = await program.account.matchAccount.fetch(matchAccount);
+    if (matchData.bracketSlot === null) {
+      console.error(`Error: Match ${argv.matchid} is not a tournament bracket match.`);
+      process.exit(1);
+    }
+
+    // Slot 0 is the final; any other slot feeds the match at (slot - 1) / 2
+    const tournamentData = await program.account.tournament.fetch(matchData.tournament);
+    const slot = matchData.bracketSlot;
+    let nextMatch = null;
+    if (slot > 0) {
+      nextMatch = tournamentData.matches[Math.floor((slot - 1) / 2)];
+      if (nextMatch.equals(PublicKey.default)) {
+        console.error(`Error: The next round match for bracket slot ${slot} has not been created yet.`);
+        console.log(`Tip: npm run create-tournament-match -- --tournamentid ${tournamentData.tournamentId} --slot ${Math.floor((slot - 1) / 2)} --matchid <MATCH_ID>`);
+        process.exit(1);
+      }
+    }
+
+    console.log(`Advancing ${argv.winner} from refunded bracket slot ${slot} of ${tournamentData.tournamentId}...`);
+
+    const tx = await program.methods
+      .advanceRefundedMatch(argv.matchid, argv.winner)
+      .accounts({
+        matchAccount: matchAccount,
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        tournament: matchData.tournament,
+        nextMatch: nextMatch,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    if (slot === 0) {
+      console.log(`${argv.winner} is the tournament champion. Anyone can now settle the winner pool:`);
+      console.log(`npm run settle-tournament-pool -- --tournamentid ${tournamentData.tournamentId}`);
+    } else {
+      console.log(`${argv.winner} advanced to the next round.`);
+    }
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("BracketSlotAdvanced")) {
+      console.error("A fighter has already gone through from this bracket slot.");
+    } else if (error.toString().includes("NotRefundable")) {
+      console.error("Only refunded bracket matches can be advanced this way. End live matches with 'npm run end-match'.");
+    } else if (error.toString().includes("InvalidWinner")) {
+      console.error("The winner must be one of the fighters in this match.");
+    } else if (error.toString().includes("Unauthorized")) {
+      console.error("You are not authorized to advance bracket matches. Only resolvers can do this.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
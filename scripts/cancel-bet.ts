@@ -0,0 +1,231 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchaccount', {
+    alias: 'a',
+    description: 'Match account public key',
+    type: 'string',
+    demandOption: true
+  })
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID',
+    type: 'string',
+    default: 'MATCH_001'
+  })
+  .option('amount', {
+    alias: 'amt',
+    description: 'Amount to take off the bet (SOL, or base units for token matches); omit to cancel the whole bet',
+    type: 'number'
+  })
+  .option('tokenaccount', {
+    description: 'Your token account for the bet mint (token matches only; default: the associated token account)',
+    type: 'string'
+  })
+  .option('treasurytokenaccount', {
+    description: 'Treasury token account that receives any cancellation fee (token matches only; default: the treasury\'s associated token account)',
+    type: 'string'
+  })
+  .option('fighter', {
+    alias: 'f',
+    description: 'Fighter of a hedge bet; omit for the wallet\'s first bet in the match',
+    type: 'string'
+  })
+  .option('keypair', {
+    alias: 'k',
+    description: 'Path to the bettor keypair file',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  let walletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+
+  // Use custom keypair if provided
+  if (argv.keypair) {
+    if (fs.existsSync(argv.keypair)) {
+      walletPath = argv.keypair;
+    } else {
+      console.log(`Keypair file not found at ${argv.keypair}`);
+      process.exit(1);
+    }
+  } else if (!fs.existsSync(walletPath)) {
+    console.log(`Wallet file not found at ${walletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = walletPath;
+
+  const wallet = new anchor.Wallet(
+    anchor.web3.Keypair.fromSecretKey(
+      new Uint8Array(JSON.parse(fs.readFileSync(walletPath, 'utf-8')))
+    )
+  );
+
+  const provider = new anchor.AnchorProvider(
+    new anchor.web3.Connection(process.env.ANCHOR_PROVIDER_URL),
+    wallet,
+    { commitment: 'confirmed' }
+  );
+
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    const matchAccount = new PublicKey(argv.matchaccount);
+
+    // Find PDA for house wallet
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    // Find the bettor's bet account for this match
+    let [betAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("bet"), matchAccount.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const mainBet = betAccount;
+
+    // A hedge bet on another fighter lives at its own address
+    if (argv.fighter) {
+      const mainBet = await program.account.bet.fetchNullable(betAccount);
+      if (!mainBet || mainBet.fighter !== argv.fighter) {
+        [betAccount] = await PublicKey.findProgramAddress(
+          [Buffer.from("bet"), matchAccount.toBuffer(), provider.wallet.publicKey.toBuffer(), Buffer.from(argv.fighter)],
+          program.programId
+        );
+      }
+    }
+
+    // Fetch match data
+    const accountInfo = await provider.connection.getAccountInfo(matchAccount);
+    if (!accountInfo) {
+      console.error(`Error: Match account not found at ${matchAccount.toString()}`);
+      process.exit(1);
+    }
+
+    const matchData = program.coder.accounts.decode('matchAccount', accountInfo.data);
+
+    // Check if match ID matches
+    if (matchData.matchId !== argv.matchid) {
+      console.error(`Error: Match ID mismatch. The match account has ID "${matchData.matchId}" but you specified "${argv.matchid}"`);
+      process.exit(1);
+    }
+
+    // Bets can only be withdrawn before betting closes
+    const currentStatus = Object.keys(matchData.status)[0];
+    const closesAt = matchData.bettingClosesAt.toNumber();
+    if (currentStatus !== 'preparation' || Math.floor(Date.now() / 1000) >= closesAt) {
+      console.error(`Error: Betting has closed for this match. Current state: ${currentStatus}`);
+      process.exit(1);
+    }
+
+    const betData = await program.account.bet.fetch(betAccount);
+    console.log(`Current bet: ${betData.amount.toString()} on ${betData.fighter}`);
+    console.log(`Cancellation fee: ${matchData.cancelFeeBps} bps`);
+
+    // Token matches pay back into the bettor's token account, and any fee into the treasury's
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const tokenAccounts = matchTokens
+      ? {
+          ...matchTokens,
+          bettorTokenAccount: payoutTokenAccount(matchTokens, provider.wallet.publicKey, argv.tokenaccount),
+          treasuryTokenAccount: matchData.cancelFeeBps > 0 || argv.treasurytokenaccount
+            ? payoutTokenAccount(matchTokens, matchData.treasury, argv.treasurytokenaccount)
+            : null,
+        }
+      : {};
+
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    const accounts = {
+      matchAccount: matchAccount,
+      bet: betAccount,
+      // The main bet keeps the wallet's totals, so hedge withdrawals update it too
+      mainBet: betAccount.equals(mainBet) ? null : mainBet,
+      houseWallet: houseWallet,
+      escrow: matchData.betMint ? null : escrow,
+      bettor: provider.wallet.publicKey,
+      treasury: matchData.treasury,
+      ...tokenAccounts,
+    };
+
+    let tx: string;
+    if (argv.amount !== undefined) {
+      const amount = matchData.betMint
+        ? new anchor.BN(argv.amount)
+        : new anchor.BN(Math.round(argv.amount * LAMPORTS_PER_SOL));
+      console.log(`Decreasing bet in match ${argv.matchid} by ${amount.toString()}...`);
+
+      tx = await program.methods
+        .decreaseBet(argv.matchid, amount)
+        .accounts(accounts)
+        .rpc();
+    } else {
+      console.log(`Cancelling bet in match ${argv.matchid}...`);
+
+      tx = await program.methods
+        .cancelBet(argv.matchid)
+        .accounts(accounts)
+        .rpc();
+    }
+
+    console.log("Transaction signature:", tx);
+    console.log(argv.amount !== undefined ? "Bet decreased successfully!" : "Bet cancelled successfully!");
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("AlreadyClaimed")) {
+      console.error("This bet has already been refunded, so there is nothing left to withdraw.");
+    } else if (error.toString().includes("HedgeBetsOpen")) {
+      console.error("Cancel your hedge bets (with --fighter) before your first bet.");
+    } else if (error.toString().includes("InvalidDecreaseAmount")) {
+      console.error("The decrease must be less than your bet. Omit --amount to cancel the whole bet.");
+    } else if (error.toString().includes("BetTooSmall")) {
+      console.error("What is left of the bet would fall below the match's minimum bet.");
+    } else if (error.toString().includes("BettingClosed") || error.toString().includes("MatchNotInPreparation")) {
+      console.error("Betting has closed for this match, so the bet can no longer be withdrawn.");
+    } else if (error.toString().includes("AccountNotInitialized")) {
+      console.error("You do not have a bet in this match.");
+    } else if (error.toString().includes("ProgramPaused")) {
+      console.error("The program is currently paused. Try again once it is unpaused.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
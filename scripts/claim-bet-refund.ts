@@ -0,0 +1,196 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchaccount', {
+    alias: 'a',
+    description: 'Match account public key',
+    type: 'string',
+    demandOption: true
+  })
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID',
+    type: 'string',
+    default: 'MATCH_001'
+  })
+  .option('fighter', {
+    alias: 'f',
+    description: 'Fighter of a hedge bet; omit for the wallet\'s first bet in the match',
+    type: 'string'
+  })
+  .option('keypair', {
+    alias: 'k',
+    description: 'Path to the bettor keypair file',
+    type: 'string'
+  })
+  .option('tokenaccount', {
+    alias: 't',
+    description: 'Your token account to pay into (token matches only; default: the associated token account)',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  let walletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+
+  // Use custom keypair if provided
+  if (argv.keypair) {
+    if (fs.existsSync(argv.keypair)) {
+      walletPath = argv.keypair;
+    } else {
+      console.log(`Keypair file not found at ${argv.keypair}`);
+      process.exit(1);
+    }
+  } else if (!fs.existsSync(walletPath)) {
+    console.log(`Wallet file not found at ${walletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = walletPath;
+
+  const wallet = new anchor.Wallet(
+    anchor.web3.Keypair.fromSecretKey(
+      new Uint8Array(JSON.parse(fs.readFileSync(walletPath, 'utf-8')))
+    )
+  );
+
+  const provider = new anchor.AnchorProvider(
+    new anchor.web3.Connection(process.env.ANCHOR_PROVIDER_URL),
+    wallet,
+    { commitment: 'confirmed' }
+  );
+
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    const matchAccount = new PublicKey(argv.matchaccount);
+
+    // Find PDA for house wallet
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    // Find the bettor's bet account for this match
+    let [betAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("bet"), matchAccount.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // A hedge bet on another fighter lives at its own address
+    if (argv.fighter) {
+      const mainBet = await program.account.bet.fetchNullable(betAccount);
+      if (!mainBet || mainBet.fighter !== argv.fighter) {
+        [betAccount] = await PublicKey.findProgramAddress(
+          [Buffer.from("bet"), matchAccount.toBuffer(), provider.wallet.publicKey.toBuffer(), Buffer.from(argv.fighter)],
+          program.programId
+        );
+      }
+    }
+
+    // Fetch match data
+    const accountInfo = await provider.connection.getAccountInfo(matchAccount);
+    if (!accountInfo) {
+      console.error(`Error: Match account not found at ${matchAccount.toString()}`);
+      process.exit(1);
+    }
+
+    const matchData = program.coder.accounts.decode('matchAccount', accountInfo.data);
+
+    // Check if match ID matches
+    if (matchData.matchId !== argv.matchid) {
+      console.error(`Error: Match ID mismatch. The match account has ID "${matchData.matchId}" but you specified "${argv.matchid}"`);
+      process.exit(1);
+    }
+
+    // Check if match is in a refundable state
+    const currentStatus = Object.keys(matchData.status)[0];
+    if (!['refund', 'draw', 'noContest'].includes(currentStatus)) {
+      console.error(`Error: Match is not refundable. Current state: ${currentStatus}`);
+      process.exit(1);
+    }
+
+    const balanceBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    console.log(`Claiming refund for ${provider.wallet.publicKey.toString()} in match ${argv.matchid}...`);
+
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    // Token matches pay out of the vault into the bettor's token account
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const bettorTokenAccount = matchTokens
+      ? payoutTokenAccount(matchTokens, provider.wallet.publicKey, argv.tokenaccount)
+      : null;
+    const tokenAccounts = matchTokens ? { ...matchTokens, bettorTokenAccount } : {};
+
+    const tx = await program.methods
+      .claimBetRefund(argv.matchid)
+      .accounts({
+        matchAccount: matchAccount,
+        bet: betAccount,
+        houseWallet: houseWallet,
+        escrow: matchData.betMint ? null : escrow,
+        bettor: provider.wallet.publicKey,
+        ...tokenAccounts,
+      })
+      .rpc();
+
+    const balanceAfter = await provider.connection.getBalance(provider.wallet.publicKey);
+
+    console.log("Transaction signature:", tx);
+    if (matchTokens) {
+      console.log(`Successfully refunded into token account ${bettorTokenAccount.toString()}.`);
+    } else {
+      console.log(`Successfully refunded ${(balanceAfter - balanceBefore) / LAMPORTS_PER_SOL} SOL (net of fees).`);
+    }
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("AlreadyClaimed")) {
+      console.error("Your stake for this match has already been refunded.");
+    } else if (error.toString().includes("NotRefundable")) {
+      console.error("The match is not in 'Refund' state.");
+    } else if (error.toString().includes("AccountNotInitialized")) {
+      console.error("You did not place a bet in this match.");
+    } else if (error.toString().includes("ProgramPaused")) {
+      console.error("The program is paused. Only refunded, drawn or no-contest matches can be refunded while paused.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
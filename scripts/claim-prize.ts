@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -32,6 +33,16 @@ const argv = yargs(hideBin(process.argv))
     type: 'string',
     default: ''
   })
+  .option('pagesize', {
+    description: 'Bets to pay per transaction',
+    type: 'number',
+    default: 10
+  })
+  .option('strict', {
+    description: 'Fail the whole transaction if any targeted bet cannot be paid',
+    type: 'boolean',
+    default: false
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -118,7 +129,23 @@ async function main() {
     }
     
     console.log(`Preparing to claim prizes for match ${argv.matchid} (${matchAccount.toString()})`);
+    // Bets on any podium fighter are paid
+    const placedFighters = matchData.placements.map(placement => placement.fighter);
     console.log(`Match winner is: ${matchData.winner}`);
+    if (placedFighters.length > 1) {
+      console.log(`Podium: ${placedFighters.join(', ')}`);
+    }
+    
+    // Token matches pay each bettor's associated token account instead of their wallet
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const payee = (bettor: PublicKey) => matchTokens ? payoutTokenAccount(matchTokens, bettor) : bettor;
+    
+    // Fetch the bets batch payouts have not walked yet, in settlement order
+    const bets = (await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: matchAccount.toBase58() } }
+    ]))
+      .filter(bet => bet.account.index >= matchData.settledCursor)
+      .sort((a, b) => a.account.index - b.account.index);
     
     // Get winners from command line or from match data
     let winnerAccounts = [];
@@ -134,24 +161,27 @@ async function main() {
         }
       });
       
-      winnerAccounts = specifiedWinners.map(pubkey => ({
-        pubkey,
-        isWritable: true,
-        isSigner: false
-      }));
+      winnerAccounts = bets
+        .filter(bet => specifiedWinners.some(pubkey => pubkey.equals(bet.account.bettor)))
+        .flatMap(bet => [
+          { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+          { pubkey: payee(bet.account.bettor), isWritable: true, isSigner: false }
+        ]);
       
-      console.log(`Processing prizes for ${winnerAccounts.length} specified winners.`);
+      console.log(`Processing prizes for ${winnerAccounts.length / 2} specified winners.`);
     } else {
-      // Get all winners from match data
-      winnerAccounts = matchData.bets
-        .filter(bet => !bet.claimed && bet.fighter === matchData.winner)
-        .map(bet => ({
-          pubkey: new PublicKey(bet.bettor),
-          isWritable: true,
-          isSigner: false
-        }));
+      // Walk every bet from the cursor; the cursor only moves over contiguous indices,
+      // so losing, claimed and cancelled bets are passed too and simply skipped
+      winnerAccounts = bets
+        .flatMap(bet => [
+          { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+          { pubkey: payee(bet.account.bettor), isWritable: true, isSigner: false }
+        ]);
       
-      console.log(`Processing prizes for all ${winnerAccounts.length} unclaimed winners.`);
+      const unclaimed = bets.filter(bet =>
+        !bet.account.claimed && !bet.account.amount.isZero() && placedFighters.includes(bet.account.fighter)
+      ).length;
+      console.log(`Walking ${bets.length} bets from the settled cursor (${unclaimed} unclaimed winners).`);
     }
     
     if (winnerAccounts.length === 0) {
@@ -160,32 +190,48 @@ async function main() {
     }
     
     // Log winners being processed
-    for (const account of winnerAccounts) {
-      console.log(`- ${account.pubkey.toString()}`);
+    for (let i = 1; i < winnerAccounts.length; i += 2) {
+      console.log(`- ${winnerAccounts[i].pubkey.toString()}`);
     }
     
     // Execute claim prize transaction
     console.log(`\nClaiming prizes for match ${argv.matchid}...`);
     
-    const tx = await program.methods
-      .claimPrize(argv.matchid)
-      .accounts({
-        matchAccount: matchAccount,
-        houseWallet: houseWallet,
-        treasury: process.env.TREASURY_WALLET,
-        authority: provider.wallet.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .remainingAccounts(winnerAccounts)
-      .rpc();
-    
-    console.log("Transaction signature:", tx);
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    // Pay in pages of (bet, bettor) pairs so large matches fit in a transaction
+    const pageAccounts = argv.pagesize * 2;
+    for (let start = 0; start < winnerAccounts.length; start += pageAccounts) {
+      const tx = await program.methods
+        .claimPrize(argv.matchid, argv.strict)
+        .accounts({
+          matchAccount: matchAccount,
+          houseWallet: houseWallet,
+          escrow: matchData.betMint ? null : escrow,
+          treasury: matchData.treasury,
+          authority: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          ...matchTokens,
+        })
+        .remainingAccounts(winnerAccounts.slice(start, start + pageAccounts))
+        .rpc();
+      console.log(`Page ${start / pageAccounts + 1} transaction signature:`, tx);
+    }
     console.log("Successfully processed prizes. Check balances of winners to confirm.");
   } catch (error) {
     console.error("Error:", error);
     
     // Provide more helpful error messages based on common error cases
-    if (error.toString().includes("MatchNotCompleted")) {
+    if (error.toString().includes("PayoutShortfall")) {
+      // The program attributes the failure to the bettor it could not pay
+      console.error(`Strict payout failed: bettor ${error.error?.origin} could not be paid, so nothing was paid out.`);
+    } else if (error.toString().includes("BetOutOfOrder")) {
+      console.error("Another payout moved the settled cursor. Run the command again to continue from it.");
+    } else if (error.toString().includes("MatchNotCompleted")) {
       console.error("The match is not in 'Completed' state. Make sure to end the match first with 'npm run end-match'.");
     } else if (error.toString().includes("InvalidMatchId")) {
       console.error("The match ID does not match. Make sure you're using the correct match ID.");
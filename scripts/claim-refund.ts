@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -32,6 +33,16 @@ const argv = yargs(hideBin(process.argv))
     type: 'string',
     default: ''
   })
+  .option('pagesize', {
+    description: 'Bets to pay per transaction',
+    type: 'number',
+    default: 10
+  })
+  .option('strict', {
+    description: 'Fail the whole transaction if any targeted bet cannot be paid',
+    type: 'boolean',
+    default: false
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -96,16 +107,27 @@ async function main() {
       process.exit(1);
     }
     
-    // Check if match is in Refund state
+    // Check if match is in a refundable state
     const currentStatus = Object.keys(matchData.status)[0];
-    if (currentStatus !== 'refund') {
-      console.error(`Error: Match is not in "Refund" state. Current state: ${currentStatus}`);
-      console.log(`Note: Only matches in "Refund" state can have refunds processed.`);
+    if (!['refund', 'draw', 'noContest'].includes(currentStatus)) {
+      console.error(`Error: Match is not refundable. Current state: ${currentStatus}`);
+      console.log(`Note: Only matches in "Refund", "Draw" or "NoContest" state can have refunds processed.`);
       process.exit(1);
     }
     
     console.log(`Preparing to claim refunds for match ${argv.matchid} (${matchAccount.toString()})`);
     
+    // Token matches pay each bettor's associated token account instead of their wallet
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const payee = (bettor: PublicKey) => matchTokens ? payoutTokenAccount(matchTokens, bettor) : bettor;
+    
+    // Fetch the bets batch payouts have not walked yet, in settlement order
+    const bets = (await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: matchAccount.toBase58() } }
+    ]))
+      .filter(bet => bet.account.index >= matchData.settledCursor)
+      .sort((a, b) => a.account.index - b.account.index);
+    
     // Get bettors from command line or from match data
     let bettorAccounts = [];
     
@@ -120,24 +142,24 @@ async function main() {
         }
       });
       
-      bettorAccounts = specifiedBettors.map(pubkey => ({
-        pubkey,
-        isWritable: true,
-        isSigner: false
-      }));
+      bettorAccounts = bets
+        .filter(bet => specifiedBettors.some(pubkey => pubkey.equals(bet.account.bettor)))
+        .flatMap(bet => [
+          { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+          { pubkey: payee(bet.account.bettor), isWritable: true, isSigner: false }
+        ]);
       
-      console.log(`Processing refunds for ${bettorAccounts.length} specified bettors.`);
+      console.log(`Processing refunds for ${bettorAccounts.length / 2} specified bettors.`);
     } else {
-      // Get all bettors from match data
-      bettorAccounts = matchData.bets
-        .filter(bet => !bet.claimed)
-        .map(bet => ({
-          pubkey: new PublicKey(bet.bettor),
-          isWritable: true,
-          isSigner: false
-        }));
+      // Walk every bet from the cursor; claimed and cancelled bets are passed too and simply skipped
+      bettorAccounts = bets
+        .flatMap(bet => [
+          { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+          { pubkey: payee(bet.account.bettor), isWritable: true, isSigner: false }
+        ]);
       
-      console.log(`Processing refunds for all ${bettorAccounts.length} unclaimed bettors.`);
+      const unclaimed = bets.filter(bet => !bet.account.claimed && !bet.account.amount.isZero()).length;
+      console.log(`Walking ${bets.length} bets from the settled cursor (${unclaimed} unclaimed refunds).`);
     }
     
     if (bettorAccounts.length === 0) {
@@ -146,25 +168,36 @@ async function main() {
     }
     
     // Log bettors being processed
-    for (const account of bettorAccounts) {
-      console.log(`- ${account.pubkey.toString()}`);
+    for (let i = 1; i < bettorAccounts.length; i += 2) {
+      console.log(`- ${bettorAccounts[i].pubkey.toString()}`);
     }
     
     // Execute claim refund transaction
     console.log(`\nClaiming refunds for match ${argv.matchid}...`);
     
-    const tx = await program.methods
-      .claimRefund(argv.matchid)
-      .accounts({
-        matchAccount: matchAccount,
-        houseWallet: houseWallet,
-        authority: provider.wallet.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .remainingAccounts(bettorAccounts)
-      .rpc();
-    
-    console.log("Transaction signature:", tx);
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    // Pay in pages of (bet, bettor) pairs so large matches fit in a transaction
+    const pageAccounts = argv.pagesize * 2;
+    for (let start = 0; start < bettorAccounts.length; start += pageAccounts) {
+      const tx = await program.methods
+        .claimRefund(argv.matchid, argv.strict)
+        .accounts({
+          matchAccount: matchAccount,
+          houseWallet: houseWallet,
+          escrow: matchData.betMint ? null : escrow,
+          authority: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          ...matchTokens,
+        })
+        .remainingAccounts(bettorAccounts.slice(start, start + pageAccounts))
+        .rpc();
+      console.log(`Page ${start / pageAccounts + 1} transaction signature:`, tx);
+    }
     console.log("Successfully processed refunds. Check balances of bettors to confirm.");
     console.log("\nTo use this match in other commands:");
     console.log(`npm run close-match -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
@@ -172,7 +205,12 @@ async function main() {
     console.error("Error:", error);
     
     // Provide more helpful error messages based on common error cases
-    if (error.toString().includes("NotRefundable")) {
+    if (error.toString().includes("PayoutShortfall")) {
+      // The program attributes the failure to the bettor it could not pay
+      console.error(`Strict payout failed: bettor ${error.error?.origin} could not be paid, so nothing was paid out.`);
+    } else if (error.toString().includes("BetOutOfOrder")) {
+      console.error("Another payout moved the settled cursor. Run the command again to continue from it.");
+    } else if (error.toString().includes("NotRefundable")) {
       console.error("The match is not in 'Refund' state. This match might not be refundable.");
     } else if (error.toString().includes("InvalidMatchId")) {
       console.error("The match ID does not match. Make sure you're using the correct match ID.");
@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -26,6 +27,15 @@ const argv = yargs(hideBin(process.argv))
     type: 'string',
     demandOption: true
   })
+  .option('pagesize', {
+    description: 'Bets to walk per transaction when advancing the settled cursor',
+    type: 'number',
+    default: 10
+  })
+  .option('treasurytokenaccount', {
+    description: 'Treasury token account that receives leftover vault tokens (token matches only; default: the treasury\'s associated token account)',
+    type: 'string'
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -81,26 +91,32 @@ async function main() {
     
     // Check if match is in Completed or Refund state
     const currentStatus = Object.keys(matchData.status)[0];
-    if (currentStatus !== 'completed' && currentStatus !== 'refund') {
+    const refundable = ['refund', 'draw', 'noContest'].includes(currentStatus);
+    if (currentStatus !== 'completed' && !refundable) {
       console.error(`Error: Match is not in a finalized state. Current state: ${currentStatus}`);
-      console.log(`Note: Only matches in "Completed" or "Refund" state can be closed.`);
+      console.log(`Note: Only matches in "Completed", "Refund", "Draw" or "NoContest" state can be closed.`);
       process.exit(1);
     }
     
+    // Fetch every bet account recorded against this match
+    const bets = await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: matchAccount.toBase58() } }
+    ]);
+    
     // Check if all prizes/refunds have been claimed
     let unclaimedCount = 0;
     if (currentStatus === 'completed') {
-      const winnerFighter = matchData.winner;
-      unclaimedCount = matchData.bets.filter(bet => 
-        !bet.claimed && bet.fighter === winnerFighter
+      const placedFighters = matchData.placements.map(placement => placement.fighter);
+      unclaimedCount = bets.filter(bet => 
+        !bet.account.claimed && !bet.account.amount.isZero() && placedFighters.includes(bet.account.fighter)
       ).length;
       
       if (unclaimedCount > 0) {
         console.error(`Error: There are ${unclaimedCount} unclaimed prizes. All prizes must be claimed before closing the match.`);
         process.exit(1);
       }
-    } else if (currentStatus === 'refund') {
-      unclaimedCount = matchData.bets.filter(bet => !bet.claimed).length;
+    } else if (refundable) {
+      unclaimedCount = bets.filter(bet => !bet.account.claimed && !bet.account.amount.isZero()).length;
       
       if (unclaimedCount > 0) {
         console.error(`Error: There are ${unclaimedCount} unclaimed refunds. All refunds must be claimed before closing the match.`);
@@ -112,16 +128,97 @@ async function main() {
     console.log(`Current match status: ${currentStatus}`);
     console.log(`All bets claimed: ${unclaimedCount === 0 ? 'Yes' : 'No'}`);
     
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+    
+    // Token matches pay each bettor's associated token account instead of their wallet
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const payee = (bettor: PublicKey) => matchTokens ? payoutTokenAccount(matchTokens, bettor) : bettor;
+    
+    // Bets can only be closed once the settled cursor has passed them, so walk any
+    // losing or cancelled bets it has not reached yet; nothing is paid out twice
+    const pending = bets
+      .filter(bet => bet.account.index >= matchData.settledCursor)
+      .sort((a, b) => a.account.index - b.account.index)
+      .flatMap(bet => [
+        { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: payee(bet.account.bettor), isWritable: true, isSigner: false }
+      ]);
+    if (pending.length > 0) {
+      console.log(`\nWalking ${pending.length / 2} bets past the settled cursor...`);
+      const pageAccounts = argv.pagesize * 2;
+      for (let start = 0; start < pending.length; start += pageAccounts) {
+        const page = pending.slice(start, start + pageAccounts);
+        if (currentStatus === 'completed') {
+          await program.methods
+            .claimPrize(argv.matchid, false)
+            .accounts({
+              matchAccount: matchAccount,
+              houseWallet: houseWallet,
+              escrow: matchData.betMint ? null : escrow,
+              treasury: matchData.treasury,
+              authority: provider.wallet.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+              ...matchTokens,
+            })
+            .remainingAccounts(page)
+            .rpc();
+        } else {
+          await program.methods
+            .claimRefund(argv.matchid, false)
+            .accounts({
+              matchAccount: matchAccount,
+              houseWallet: houseWallet,
+              escrow: matchData.betMint ? null : escrow,
+              authority: provider.wallet.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+              ...matchTokens,
+            })
+            .remainingAccounts(page)
+            .rpc();
+        }
+      }
+    }
+    
+    // Close every settled bet account first, returning its rent to the bettor
+    if (bets.length > 0) {
+      console.log(`\nClosing ${bets.length} bet accounts...`);
+      for (const bet of bets) {
+        await program.methods
+          .closeBetAccount(argv.matchid)
+          .accounts({
+            matchAccount: matchAccount,
+            bet: bet.publicKey,
+            bettor: bet.account.bettor,
+          })
+          .rpc();
+      }
+    }
+    
     // Execute close match account transaction
     console.log(`\nClosing match account...`);
     
+    // Leftover vault tokens are swept to the treasury's token account before the vault is closed
+    const tokenAccounts = matchTokens
+      ? {
+          ...matchTokens,
+          treasuryTokenAccount: payoutTokenAccount(matchTokens, matchData.treasury, argv.treasurytokenaccount),
+        }
+      : {};
+    
     const tx = await program.methods
       .closeMatchAccount(argv.matchid)
       .accounts({
         matchAccount: matchAccount,
         houseWallet: houseWallet,
+        escrow: matchData.betMint ? null : escrow,
+        treasury: matchData.treasury,
         authority: provider.wallet.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
+        ...tokenAccounts,
       })
       .rpc();
     
@@ -137,6 +234,8 @@ async function main() {
       console.error("There are unclaimed prizes. All prizes must be claimed before closing the match.");
     } else if (error.toString().includes("UnclaimedRefunds")) {
       console.error("There are unclaimed refunds. All refunds must be claimed before closing the match.");
+    } else if (error.toString().includes("BetNotSettled")) {
+      console.error("A bet has not been reached by the settled cursor yet. Run this command again to walk the remaining bets.");
     } else if (error.toString().includes("InvalidMatchId")) {
       console.error("The match ID does not match. Make sure you're using the correct match ID.");
     } else if (error.toString().includes("Unauthorized")) {
@@ -32,6 +32,45 @@ const argv = yargs(hideBin(process.argv))
     type: 'string',
     default: 'SHIB'
   })
+  .option('fighters', {
+    description: 'Comma-separated fighter names for a battle royale (2-16); overrides --fighter1/--fighter2',
+    type: 'string'
+  })
+  .option('opens', {
+    description: 'Unix timestamp when betting opens (default: now)',
+    type: 'number'
+  })
+  .option('closes', {
+    description: 'Unix timestamp when betting closes (default: one hour after opening)',
+    type: 'number'
+  })
+  .option('ends', {
+    description: 'Unix timestamp when the match is expected to end (default: one hour after betting closes)',
+    type: 'number'
+  })
+  .option('deadline', {
+    description: 'Unix timestamp after which anyone can refund an unresolved match (default: one day after the expected end)',
+    type: 'number'
+  })
+  .option('mint', {
+    description: 'Bet mint for a token match (SPL Token or Token-2022); omit for a SOL match',
+    type: 'string'
+  })
+  .option('minbet', {
+    description: 'Minimum bet in base units of the mint (token matches only)',
+    type: 'number',
+    default: 1
+  })
+  .option('maxbet', {
+    description: 'Maximum bet in base units of the mint (token matches only)',
+    type: 'string',
+    default: '18446744073709551615'
+  })
+  .option('maxfee', {
+    description: 'Maximum fee in base units of the mint (token matches only)',
+    type: 'string',
+    default: '18446744073709551615'
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -60,23 +99,27 @@ async function main() {
   const program = new Program(idl as any, provider);
   
   // Validate inputs
-  if (argv.fighter1 === argv.fighter2) {
-    console.error(`Error: Fighter 1 and Fighter 2 cannot be the same "${argv.fighter1}"`);
-    process.exit(1);
-  }
+  const fighters: string[] = argv.fighters
+    ? argv.fighters.split(',').map(name => name.trim())
+    : [argv.fighter1, argv.fighter2];
   
   if (!argv.matchid || argv.matchid.trim() === '') {
     console.error('Error: Match ID cannot be empty');
     process.exit(1);
   }
   
-  if (!argv.fighter1 || argv.fighter1.trim() === '') {
-    console.error('Error: Fighter 1 name cannot be empty');
+  if (fighters.length < 2 || fighters.length > 16) {
+    console.error(`Error: A match needs between 2 and 16 fighters, got ${fighters.length}`);
+    process.exit(1);
+  }
+  
+  if (fighters.some(name => !name || name.trim() === '')) {
+    console.error('Error: Fighter names cannot be empty');
     process.exit(1);
   }
   
-  if (!argv.fighter2 || argv.fighter2.trim() === '') {
-    console.error('Error: Fighter 2 name cannot be empty');
+  if (new Set(fighters).size !== fighters.length) {
+    console.error(`Error: Fighter names must be unique: ${fighters.join(', ')}`);
     process.exit(1);
   }
   
@@ -104,30 +147,91 @@ async function main() {
       program.programId
     );
     
+    // Betting window; bets outside it are rejected on-chain
+    const opensAt = argv.opens ?? Math.floor(Date.now() / 1000);
+    const closesAt = argv.closes ?? opensAt + 3600;
+    const endsAt = argv.ends ?? closesAt + 3600;
+    const deadline = argv.deadline ?? endsAt + 86400;
+    const schedule = {
+      bettingOpensAt: new anchor.BN(opensAt),
+      bettingClosesAt: new anchor.BN(closesAt),
+      expectedEndAt: new anchor.BN(endsAt),
+      resolutionDeadline: new anchor.BN(deadline),
+    };
+
     console.log(`Creating match:`);
     console.log(`- Match ID: ${argv.matchid}`);
-    console.log(`- Fighter 1: ${argv.fighter1}`);
-    console.log(`- Fighter 2: ${argv.fighter2}`);
+    console.log(`- Fighters: ${fighters.join(', ')}`);
+    console.log(`- Betting opens: ${new Date(opensAt * 1000).toISOString()}`);
+    console.log(`- Betting closes: ${new Date(closesAt * 1000).toISOString()}`);
+    console.log(`- Expected end: ${new Date(endsAt * 1000).toISOString()}`);
+    console.log(`- Resolution deadline: ${new Date(deadline * 1000).toISOString()}`);
     console.log("Match account (PDA):", matchAccount.toString());
     
-    // Create match account
-    const tx = await program.methods
-      .createMatchAccount(argv.matchid, argv.fighter1, argv.fighter2)
-      .accounts({
-        matchAccount: matchAccount,
-        authority: provider.wallet.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
-        houseWallet: houseWallet,
-      })
-      .rpc();
+    let tx: string;
+    if (argv.mint) {
+      // Token match: the vault is a token account owned by the match PDA
+      const betMint = new PublicKey(argv.mint);
+      const mintInfo = await provider.connection.getAccountInfo(betMint);
+      if (!mintInfo) {
+        console.error(`Error: Mint not found at ${betMint.toString()}`);
+        process.exit(1);
+      }
+      const [vault] = await PublicKey.findProgramAddress(
+        [Buffer.from("vault"), matchAccount.toBuffer()],
+        program.programId
+      );
+      console.log(`- Bet mint: ${betMint.toString()}`);
+      console.log("Vault (PDA):", vault.toString());
+
+      tx = await program.methods
+        .createTokenMatchAccount(
+          argv.matchid,
+          fighters,
+          new anchor.BN(argv.minbet),
+          new anchor.BN(argv.maxbet),
+          new anchor.BN(argv.maxfee),
+          schedule
+        )
+        .accounts({
+          matchAccount: matchAccount,
+          betMint: betMint,
+          vault: vault,
+          houseWallet: houseWallet,
+          authority: provider.wallet.publicKey,
+          // The mint's owner tells us whether this is SPL Token or Token-2022
+          tokenProgram: mintInfo.owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    } else {
+      // SOL stakes are held in the match's own escrow PDA
+      const [escrow] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), matchAccount.toBuffer()],
+        program.programId
+      );
+
+      // Create match account
+      tx = await program.methods
+        .createMatchAccount(argv.matchid, fighters, schedule)
+        .accounts({
+          matchAccount: matchAccount,
+          escrow: escrow,
+          authority: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          houseWallet: houseWallet,
+        })
+        .rpc();
+    }
     
     console.log("Transaction signature:", tx);
     console.log(`\nMatch created successfully!`);
     
     console.log("\nTo use this match in other commands:");
-    console.log(`npm run place-bet -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid} --fighter ${argv.fighter1}`);
+    console.log(`npm run place-bet -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid} --fighter ${fighters[0]}`);
     console.log(`npm run update-status -- --matchaccount ${matchAccount.toString()}`);
-    console.log(`npm run end-match -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid} --winner ${argv.fighter1}`);
+    console.log(`npm run start-battle -- --matchid ${argv.matchid}`);
+    console.log(`npm run end-match -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid} --winner ${fighters[0]}`);
     console.log(`npm run claim-prize -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
     console.log(`npm run claim-refund -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
     console.log(`npm run close-match -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
@@ -140,6 +244,8 @@ async function main() {
       console.log("To unpause: npm run set-pause-state -- --paused false");
     } else if (error.toString().includes("DuplicateMatch")) {
       console.error(`A match with ID "${argv.matchid}" already exists. Please choose a different match ID.`);
+    } else if (error.toString().includes("InvalidSchedule")) {
+      console.error("The schedule must run opens < closes <= expected end <= resolution deadline.");
     }
     
     process.exit(1);
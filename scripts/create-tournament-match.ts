@@ -0,0 +1,164 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('tournamentid', {
+    alias: 't',
+    description: 'Tournament ID',
+    type: 'string',
+    default: 'CUP_001'
+  })
+  .option('slot', {
+    alias: 's',
+    description: 'Bracket slot (0 is the final; slot k is fed by slots 2k+1 and 2k+2)',
+    type: 'number',
+    demandOption: true
+  })
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID for this bracket match',
+    type: 'string',
+    demandOption: true
+  })
+  .option('opens', {
+    description: 'Unix timestamp when betting opens (default: now)',
+    type: 'number'
+  })
+  .option('closes', {
+    description: 'Unix timestamp when betting closes (default: one hour after opening)',
+    type: 'number'
+  })
+  .option('ends', {
+    description: 'Unix timestamp when the match is expected to end (default: one hour after betting closes)',
+    type: 'number'
+  })
+  .option('deadline', {
+    description: 'Unix timestamp after which anyone can refund an unresolved match (default: one day after the expected end)',
+    type: 'number'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+  
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+  
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+  
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+  
+  try {
+    // Find PDAs for the tournament, match and house wallet
+    const [tournament] = await PublicKey.findProgramAddress(
+      [Buffer.from("tournament"), Buffer.from(argv.tournamentid)],
+      program.programId
+    );
+    const [matchAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(argv.matchid)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+    
+    const tournamentData = await program.account.tournament.fetch(tournament);
+    if (argv.slot < 0 || argv.slot >= tournamentData.matches.length) {
+      console.error(`Error: Slot must be between 0 and ${tournamentData.matches.length - 1}`);
+      process.exit(1);
+    }
+    
+    const opensAt = argv.opens ?? Math.floor(Date.now() / 1000);
+    const closesAt = argv.closes ?? opensAt + 3600;
+    const endsAt = argv.ends ?? closesAt + 3600;
+    const deadline = argv.deadline ?? endsAt + 86400;
+    const schedule = {
+      bettingOpensAt: new anchor.BN(opensAt),
+      bettingClosesAt: new anchor.BN(closesAt),
+      expectedEndAt: new anchor.BN(endsAt),
+      resolutionDeadline: new anchor.BN(deadline),
+    };
+    
+    console.log(`Creating bracket match:`);
+    console.log(`- Tournament ID: ${argv.tournamentid}`);
+    console.log(`- Slot: ${argv.slot}`);
+    console.log(`- Match ID: ${argv.matchid}`);
+    console.log("Match account (PDA):", matchAccount.toString());
+    
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .createTournamentMatch(argv.tournamentid, argv.slot, argv.matchid, schedule)
+      .accounts({
+        tournament: tournament,
+        matchAccount: matchAccount,
+        escrow: escrow,
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    
+    console.log("Transaction signature:", tx);
+    console.log(`\nBracket match created successfully!`);
+    
+    const firstRound = tournamentData.fighters.length / 2 - 1;
+    if (argv.slot < firstRound) {
+      console.log("Fighters are filled in as the feeder matches end; betting opens once both are known.");
+    }
+  } catch (error) {
+    console.error("Error:", error);
+    
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("ProgramPaused")) {
+      console.error("Program is currently paused. New matches cannot be created.");
+    } else if (error.toString().includes("BracketSlotTaken")) {
+      console.error(`Bracket slot ${argv.slot} already has a match.`);
+    } else if (error.toString().includes("InvalidBracketSlot")) {
+      console.error(`Bracket slot ${argv.slot} does not exist in this tournament.`);
+    } else if (error.toString().includes("InvalidSchedule")) {
+      console.error("The schedule must run opens < closes <= expected end <= resolution deadline.");
+    } else if (error.toString().includes("Unauthorized")) {
+      console.error("Only operators can create tournament matches.");
+    }
+    
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,179 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('tournamentid', {
+    alias: 't',
+    description: 'Tournament ID',
+    type: 'string',
+    default: 'CUP_001'
+  })
+  .option('poolid', {
+    alias: 'p',
+    description: 'Match ID for the outright tournament winner pool',
+    type: 'string'
+  })
+  .option('fighters', {
+    alias: 'f',
+    description: 'Comma-separated fighters in seeding order (2, 4, 8 or 16)',
+    type: 'string',
+    demandOption: true
+  })
+  .option('opens', {
+    description: 'Unix timestamp when winner pool betting opens (default: now)',
+    type: 'number'
+  })
+  .option('closes', {
+    description: 'Unix timestamp when winner pool betting closes (default: one hour after opening)',
+    type: 'number'
+  })
+  .option('ends', {
+    description: 'Unix timestamp when the tournament is expected to end (default: one day after betting closes)',
+    type: 'number'
+  })
+  .option('deadline', {
+    description: 'Unix timestamp after which anyone can refund an unsettled winner pool (default: one day after the expected end)',
+    type: 'number'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+  
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+  
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+  
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+  
+  // Validate inputs
+  const fighters: string[] = argv.fighters.split(',').map(name => name.trim());
+  const poolId = argv.poolid ?? `${argv.tournamentid}_WIN`;
+  
+  if (![2, 4, 8, 16].includes(fighters.length)) {
+    console.error(`Error: A bracket needs 2, 4, 8 or 16 fighters, got ${fighters.length}`);
+    process.exit(1);
+  }
+  
+  if (fighters.some(name => !name)) {
+    console.error('Error: Fighter names cannot be empty');
+    process.exit(1);
+  }
+  
+  if (new Set(fighters).size !== fighters.length) {
+    console.error(`Error: Fighter names must be unique: ${fighters.join(', ')}`);
+    process.exit(1);
+  }
+  
+  try {
+    // Find PDAs for the tournament, winner pool and house wallet
+    const [tournament] = await PublicKey.findProgramAddress(
+      [Buffer.from("tournament"), Buffer.from(argv.tournamentid)],
+      program.programId
+    );
+    const [winnerPool] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(poolId)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+    
+    // Winner pool betting window
+    const opensAt = argv.opens ?? Math.floor(Date.now() / 1000);
+    const closesAt = argv.closes ?? opensAt + 3600;
+    const endsAt = argv.ends ?? closesAt + 86400;
+    const deadline = argv.deadline ?? endsAt + 86400;
+    const schedule = {
+      bettingOpensAt: new anchor.BN(opensAt),
+      bettingClosesAt: new anchor.BN(closesAt),
+      expectedEndAt: new anchor.BN(endsAt),
+      resolutionDeadline: new anchor.BN(deadline),
+    };
+    
+    console.log(`Creating tournament:`);
+    console.log(`- Tournament ID: ${argv.tournamentid}`);
+    console.log(`- Fighters: ${fighters.join(', ')}`);
+    console.log(`- Winner pool match ID: ${poolId}`);
+    console.log("Tournament (PDA):", tournament.toString());
+    console.log("Winner pool (PDA):", winnerPool.toString());
+    
+    // SOL stakes are held in the match's own escrow PDA
+    const [winnerPoolEscrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), winnerPool.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .createTournament(argv.tournamentid, poolId, fighters, schedule)
+      .accounts({
+        tournament: tournament,
+        winnerPool: winnerPool,
+        winnerPoolEscrow: winnerPoolEscrow,
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    
+    console.log("Transaction signature:", tx);
+    console.log(`\nTournament created successfully!`);
+    
+    // First round slots are the second half of the bracket
+    const firstRound = fighters.length / 2 - 1;
+    console.log(`\nBracket slots: 0 is the final, first round is slots ${firstRound}-${fighters.length - 2}.`);
+    console.log("Create each bracket match with:");
+    console.log(`npm run create-tournament-match -- --tournamentid ${argv.tournamentid} --slot ${firstRound} --matchid ${argv.tournamentid}_M${firstRound}`);
+    console.log("Bet on the outright winner with:");
+    console.log(`npm run place-bet -- --matchaccount ${winnerPool.toString()} --matchid ${poolId} --fighter ${fighters[0]}`);
+  } catch (error) {
+    console.error("Error:", error);
+    
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("ProgramPaused")) {
+      console.error("Program is currently paused. New tournaments cannot be created.");
+    } else if (error.toString().includes("InvalidBracketSize")) {
+      console.error("A bracket needs 2, 4, 8 or 16 fighters.");
+    } else if (error.toString().includes("Unauthorized")) {
+      console.error("Only operators can create tournaments.");
+    } else if (error.toString().includes("already in use")) {
+      console.error(`Tournament "${argv.tournamentid}" or match "${poolId}" already exists.`);
+    }
+    
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -31,6 +32,16 @@ const argv = yargs(hideBin(process.argv))
     description: 'Path to keypair file (authority)',
     type: 'string'
   })
+  .option('pagesize', {
+    description: 'Bets to pay per transaction',
+    type: 'number',
+    default: 10
+  })
+  .option('strict', {
+    description: 'Fail the whole transaction if any targeted bet cannot be paid',
+    type: 'boolean',
+    default: false
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -128,52 +139,67 @@ async function main() {
       process.exit(1);
     }
     
+    // Token matches pay each bettor's associated token account instead of their wallet
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const payee = (bettor: PublicKey) => matchTokens ? payoutTokenAccount(matchTokens, bettor) : bettor;
+    
+    // Fetch the bets batch payouts have not walked yet, in settlement order
+    const bets = (await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: matchAccount.toBase58() } }
+    ]))
+      .filter(bet => bet.account.index >= matchData.settledCursor)
+      .sort((a, b) => a.account.index - b.account.index);
+    
     // Display match info
     const currentStatus = Object.keys(matchData.status)[0];
     
     console.log(`Match Information:`);
     console.log(`- Match ID: ${matchData.matchId}`);
-    console.log(`- Fighter 1: ${matchData.fighter1}`);
-    console.log(`- Fighter 2: ${matchData.fighter2}`);
+    console.log(`- Fighters: ${matchData.fighters.map(pool => pool.name).join(', ')}`);
     console.log(`- Status: ${currentStatus}`);
-    console.log(`- Total Bets Fighter 1: ${matchData.totalBetsFighter1 / anchor.web3.LAMPORTS_PER_SOL} SOL`);
-    console.log(`- Total Bets Fighter 2: ${matchData.totalBetsFighter2 / anchor.web3.LAMPORTS_PER_SOL} SOL`);
-    console.log(`- Number of bets: ${matchData.bets.length}`);
+    for (const pool of matchData.fighters) {
+      console.log(`- Total Bets ${pool.name}: ${pool.totalBets / anchor.web3.LAMPORTS_PER_SOL} SOL`);
+    }
+    console.log(`- Bets from settled cursor ${matchData.settledCursor}: ${bets.length}`);
     
     console.log(`\nInitiating emergency refund for match ${argv.matchid}...`);
     
-    // Collect bettor accounts for all unclaimed bets
-    const remainingAccounts = [];
-    for (const bet of matchData.bets) {
-      if (!bet.claimed) {
-        remainingAccounts.push({
-          pubkey: bet.bettor,
-          isWritable: true,
-          isSigner: false
-        });
-      }
-    }
+    // Walk every bet from the cursor; claimed and cancelled bets are passed too and simply skipped
+    const remainingAccounts = bets.flatMap(bet => [
+      { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+      { pubkey: payee(bet.account.bettor), isWritable: true, isSigner: false }
+    ]);
     
     if (remainingAccounts.length === 0) {
-      console.log("No unclaimed bets found. Nothing to refund.");
+      console.log("No bets left to walk. Nothing to refund.");
       process.exit(0);
     }
     
-    console.log(`Found ${remainingAccounts.length} unclaimed bets to refund.`);
-    
-    // Perform emergency refund
-    const tx = await program.methods
-      .emergencyRefund(argv.matchid)
-      .accounts({
-        matchAccount: matchAccount,
-        houseWallet: houseWallet,
-        authority: provider.wallet.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .remainingAccounts(remainingAccounts)
-      .rpc();
-    
-    console.log("Transaction signature:", tx);
+    console.log(`Walking ${remainingAccounts.length / 2} bets from the settled cursor.`);
+    
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    // Pay in pages of (bet, bettor) pairs so large matches fit in a transaction
+    const pageAccounts = argv.pagesize * 2;
+    for (let start = 0; start < remainingAccounts.length; start += pageAccounts) {
+      const tx = await program.methods
+        .emergencyRefund(argv.matchid, argv.strict)
+        .accounts({
+          matchAccount: matchAccount,
+          houseWallet: houseWallet,
+          escrow: matchData.betMint ? null : escrow,
+          authority: provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          ...matchTokens,
+        })
+        .remainingAccounts(remainingAccounts.slice(start, start + pageAccounts))
+        .rpc();
+      console.log(`Page ${start / pageAccounts + 1} transaction signature:`, tx);
+    }
     console.log(`Success: Emergency refund initiated for match ${argv.matchid}.`);
     console.log(`All eligible bettors have been refunded.`);
 
@@ -182,6 +208,12 @@ async function main() {
     
   } catch (error) {
     console.error("Error:", error);
+    
+    if (error.toString().includes("PayoutShortfall")) {
+      // The program attributes the failure to the bettor it could not pay
+      console.error(`Strict payout failed: bettor ${error.error?.origin} could not be paid, so nothing was paid out.`);
+    }
+    
     process.exit(1);
   }
 }
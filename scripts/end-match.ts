@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -29,6 +30,21 @@ const argv = yargs(hideBin(process.argv))
     description: 'Winner fighter name',
     type: 'string'
   })
+  .option('placements', {
+    alias: 'p',
+    description: 'Comma-separated fighters in finishing order, one per payout place',
+    type: 'string'
+  })
+  .option('result', {
+    alias: 'r',
+    description: 'End without a winner (draw or no contest)',
+    type: 'string',
+    choices: ['draw', 'nocontest']
+  })
+  .option('treasurytokenaccount', {
+    description: 'Treasury token account that receives the fee (token matches only; default: the treasury\'s associated token account)',
+    type: 'string'
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -79,8 +95,7 @@ async function main() {
     
     console.log(`Match data:`);
     console.log(`- Match ID: ${matchData.matchId}`);
-    console.log(`- Fighter 1: ${matchData.fighter1}`);
-    console.log(`- Fighter 2: ${matchData.fighter2}`);
+    console.log(`- Fighters: ${matchData.fighters.map(pool => pool.name).join(', ')}`);
     console.log(`- Status: ${Object.keys(matchData.status)[0]}`);
     
     // Check if match ID matches
@@ -90,10 +105,22 @@ async function main() {
     }
     
     // Verify the winner is one of the fighters
-    const validWinners = [matchData.fighter1, matchData.fighter2];
-    if (!validWinners.includes(argv.winner)) {
+    const validWinners = matchData.fighters.map(pool => pool.name);
+    const placements: string[] = argv.placements
+      ? argv.placements.split(',').map(name => name.trim())
+      : [];
+    const invalidPlacement = placements.find(name => !validWinners.includes(name));
+    if (invalidPlacement) {
+      console.error(`Error: Invalid placement "${invalidPlacement}". Valid options are: ${validWinners.join(', ')}`);
+      process.exit(1);
+    }
+    if (placements.length > 0 && placements.length !== matchData.payoutSplit.length) {
+      console.error(`Error: This match pays ${matchData.payoutSplit.length} places; got ${placements.length} placements.`);
+      process.exit(1);
+    }
+    if (!argv.result && placements.length === 0 && !validWinners.includes(argv.winner)) {
       console.error(`Error: Invalid winner "${argv.winner}". Valid options are: ${validWinners.join(', ')}`);
-      console.log(`Tip: Use --winner ${validWinners[0]} or --winner ${validWinners[1]}`);
+      console.log(`Tip: Use --winner ${validWinners[0]}`);
       process.exit(1);
     }
     
@@ -105,30 +132,94 @@ async function main() {
       process.exit(1);
     }
     
-    console.log(`Ending match ${argv.matchid} with winner ${argv.winner}`);
+    // A draw or no contest refunds stakes under the match's draw handling
+    let result;
+    if (argv.result === 'draw') {
+      result = { draw: {} };
+      console.log(`Ending match ${argv.matchid} as a draw`);
+    } else if (argv.result === 'nocontest') {
+      result = { noContest: {} };
+      console.log(`Ending match ${argv.matchid} as a no contest`);
+    } else if (placements.length > 0) {
+      result = { placements: { 0: placements } };
+      console.log(`Ending match ${argv.matchid} with placements ${placements.join(', ')}`);
+    } else {
+      result = { winner: { 0: argv.winner } };
+      console.log(`Ending match ${argv.matchid} with winner ${argv.winner}`);
+    }
+    
+    // Bracket matches advance their winner into the next round's match
+    let tournament = null;
+    let nextMatch = null;
+    if (matchData.bracketSlot !== null) {
+      tournament = matchData.tournament;
+      const tournamentData = await program.account.tournament.fetch(tournament);
+      const slot = matchData.bracketSlot;
+      if (slot > 0) {
+        nextMatch = tournamentData.matches[Math.floor((slot - 1) / 2)];
+        if (nextMatch.equals(PublicKey.default)) {
+          console.error(`Error: The next round match for bracket slot ${slot} has not been created yet.`);
+          console.log(`Tip: npm run create-tournament-match -- --tournamentid ${tournamentData.tournamentId} --slot ${Math.floor((slot - 1) / 2)} --matchid <MATCH_ID>`);
+          process.exit(1);
+        }
+      }
+    }
     
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    // Token matches take the fee out of the vault into the treasury's token account
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const tokenAccounts = matchTokens
+      ? {
+          ...matchTokens,
+          treasuryTokenAccount: payoutTokenAccount(matchTokens, matchData.treasury, argv.treasurytokenaccount),
+        }
+      : {};
+
     // End match
     const tx = await program.methods
-      .endMatch(argv.matchid, argv.winner)
+      .endMatch(argv.matchid, result)
       .accounts({
         matchAccount: matchAccount,
         houseWallet: houseWallet,
-        treasury: provider.wallet.publicKey,
+        escrow: matchData.betMint ? null : escrow,
+        treasury: matchData.treasury,
         authority: provider.wallet.publicKey,
+        tournament: tournament,
+        nextMatch: nextMatch,
         systemProgram: anchor.web3.SystemProgram.programId,
+        ...tokenAccounts,
       })
       .rpc();
     
     console.log("Transaction signature:", tx);
-    console.log(`Match ended successfully with ${argv.winner} as winner!`);
-    console.log(`\nNext steps:`);
-    console.log(`- To claim prizes: npm run claim-prize -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
+    if (argv.result) {
+      console.log(`Match ended without a winner (${argv.result}). Stakes are refundable.`);
+      console.log(`\nNext steps:`);
+      console.log(`- To refund bettors: npm run claim-refund -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
+    } else {
+      console.log(`Match ended successfully with ${placements[0] ?? argv.winner} as winner!`);
+      console.log(`\nNext steps:`);
+      console.log(`- To claim prizes: npm run claim-prize -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
+    }
     console.log(`- To close the match: npm run close-match -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid}`);
   } catch (error) {
     console.error("Error:", error);
     
     // Provide more helpful error messages based on common error cases
-    if (error.toString().includes("InvalidWinner")) {
+    if (error.toString().includes("TournamentMatchNeedsWinner")) {
+      console.error("Tournament bracket matches must end with a single winner.");
+    } else if (error.toString().includes("TournamentPoolSettlesItself")) {
+      console.error("Tournament winner pools settle from the final. Use 'npm run settle-tournament-pool' instead.");
+    } else if (error.toString().includes("FightersNotDecided")) {
+      console.error("This bracket match is still waiting on a feeder match's winner.");
+    } else if (error.toString().includes("InvalidPlacements")) {
+      console.error("Placements must name one distinct fighter per place in the match's payout split.");
+    } else if (error.toString().includes("InvalidWinner")) {
       console.error("Invalid winner. Make sure the winner is one of the fighters in this match.");
     } else if (error.toString().includes("InvalidMatchId")) {
       console.error("Invalid match ID. Make sure the match ID matches what was used when creating the match.");
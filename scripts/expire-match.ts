@@ -0,0 +1,124 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID',
+    type: 'string',
+    default: 'MATCH_001'
+  })
+  .option('keypair', {
+    alias: 'k',
+    description: 'Path to the keypair paying for the transaction',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  let walletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+
+  // Use custom keypair if provided
+  if (argv.keypair) {
+    if (fs.existsSync(argv.keypair)) {
+      walletPath = argv.keypair;
+    } else {
+      console.log(`Keypair file not found at ${argv.keypair}`);
+      process.exit(1);
+    }
+  } else if (!fs.existsSync(walletPath)) {
+    console.log(`Wallet file not found at ${walletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = walletPath;
+
+  const wallet = new anchor.Wallet(
+    anchor.web3.Keypair.fromSecretKey(
+      new Uint8Array(JSON.parse(fs.readFileSync(walletPath, 'utf-8')))
+    )
+  );
+
+  const provider = new anchor.AnchorProvider(
+    new anchor.web3.Connection(process.env.ANCHOR_PROVIDER_URL),
+    wallet,
+    { commitment: 'confirmed' }
+  );
+
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDA for match account
+    const [matchAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(argv.matchid)],
+      program.programId
+    );
+
+    // Fetch match data
+    const accountInfo = await provider.connection.getAccountInfo(matchAccount);
+    if (!accountInfo) {
+      console.error(`Error: Match account not found at ${matchAccount.toString()}`);
+      process.exit(1);
+    }
+
+    const matchData = program.coder.accounts.decode('matchAccount', accountInfo.data);
+    const deadline = matchData.resolutionDeadline.toNumber();
+    if (Math.floor(Date.now() / 1000) < deadline) {
+      console.error(`Error: The resolution deadline is ${new Date(deadline * 1000).toISOString()}`);
+      process.exit(1);
+    }
+
+    console.log(`Expiring unresolved match ${argv.matchid}...`);
+
+    const tx = await program.methods
+      .expireMatch(argv.matchid)
+      .accounts({
+        matchAccount: matchAccount,
+        cranker: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    console.log("Match status updated to Refund. Bettors can now recover their stakes:");
+    console.log(`npm run claim-bet-refund -- --matchaccount ${matchAccount.toString()} --matchid ${argv.matchid} --keypair <BETTOR_KEYPAIR>`);
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("ResolutionDeadlineNotReached")) {
+      console.error("The resolution deadline for this match has not passed yet.");
+    } else if (error.toString().includes("MatchNotFinalized")) {
+      console.error("Match has already been completed or refunded.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
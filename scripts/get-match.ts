@@ -59,21 +59,52 @@ async function main() {
     console.log("=== Match Information ===");
     console.log(`Match Account: ${matchAccount.toString()}`);
     console.log(`Match ID: ${matchData.matchId}`);
-    console.log(`Fighter 1: ${matchData.fighter1}`);
-    console.log(`Fighter 2: ${matchData.fighter2 || 'Not set'}`);
+    console.log(`Fighters: ${matchData.fighters.map(pool => pool.name || 'TBD').join(', ')}`);
     console.log(`Status: ${Object.keys(matchData.status)[0]}`);
     console.log(`Winner: ${matchData.winner ? matchData.winner : 'Not set'}`);
-    console.log(`Total bets on ${matchData.fighter1}: ${matchData.totalBetsFighter1.toString()} lamports`);
-    console.log(`Total bets on ${matchData.fighter2 || 'Fighter 2'}: ${matchData.totalBetsFighter2.toString()} lamports`);
+    console.log(`Payout split: ${matchData.payoutSplit.map(bps => `${bps / 100}%`).join(' / ')}`);
+    matchData.placements.forEach((placement, index) => {
+      console.log(`Place ${index + 1}: ${placement.fighter} (prize pool ${placement.prizePool.toString()} lamports)`);
+    });
+    for (const pool of matchData.fighters) {
+      console.log(`Total bets on ${pool.name}: ${pool.totalBets.toString()} lamports (${pool.betCount} bets)`);
+    }
     console.log(`Prize pool: ${matchData.prizePool.toString()} lamports`);
+    console.log(`Settled cursor: ${matchData.settledCursor} of ${matchData.nextBetIndex} bets`);
+    console.log(`Paid out: ${matchData.paidOut.toString()} (${matchData.claimedCount} payouts)`);
+    if (!matchData.betMint) {
+      const [escrow] = await PublicKey.findProgramAddress(
+        [Buffer.from("escrow"), matchAccount.toBuffer()],
+        program.programId
+      );
+      const escrowBalance = await provider.connection.getBalance(escrow);
+      console.log(`Escrow: ${escrow.toString()} (${escrowBalance} lamports incl. rent)`);
+    }
+    console.log(`Draw handling: ${Object.keys(matchData.drawHandling)[0]}`);
+    console.log(`Cancellation fee: ${matchData.cancelFeeBps} bps`);
+    console.log(`Hedging: ${Object.keys(matchData.hedgingPolicy)[0]}`);
+    console.log(`Bet limits: ${matchData.minBet.toString()}-${matchData.maxBet.toString()} per bet, ${matchData.maxWalletTotal.toString()} per wallet, ${matchData.maxFighterPool.toString()} per fighter`);
+    console.log(`Betting opens: ${new Date(matchData.bettingOpensAt.toNumber() * 1000).toISOString()}`);
+    console.log(`Betting closes: ${new Date(matchData.bettingClosesAt.toNumber() * 1000).toISOString()}`);
+    console.log(`Expected end: ${new Date(matchData.expectedEndAt.toNumber() * 1000).toISOString()}`);
+    console.log(`Resolution deadline: ${new Date(matchData.resolutionDeadline.toNumber() * 1000).toISOString()}`);
+    if (matchData.tournament) {
+      const role = matchData.bracketSlot === null ? 'winner pool' : `bracket slot ${matchData.bracketSlot}`;
+      console.log(`Tournament: ${matchData.tournament.toString()} (${role})`);
+    }
     
     // Display all bets
     console.log("\n=== Bets ===");
-    if (matchData.bets.length === 0) {
+    const bets = (await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: matchAccount.toBase58() } }
+    ]))
+      .map(bet => bet.account)
+      .sort((a, b) => a.index - b.index);
+    if (bets.length === 0) {
       console.log("No bets placed yet");
     } else {
-      matchData.bets.forEach((bet, index) => {
-        console.log(`Bet #${index + 1}:`);
+      bets.forEach(bet => {
+        console.log(`Bet #${bet.index}:`);
         console.log(`  Bettor: ${bet.bettor.toString()}`);
         console.log(`  Amount: ${bet.amount.toString()} lamports (${bet.amount.toNumber() / anchor.web3.LAMPORTS_PER_SOL} SOL)`);
         console.log(`  Fighter: ${bet.fighter}`);
@@ -86,12 +117,11 @@ async function main() {
     if (status === 'preparation') {
       console.log(`Update match status to Battle: npm run update-status -- --matchaccount ${matchAccount.toString()} --status Battle`);
     } else if (status === 'battle') {
-      console.log(`End match: npm run end-match -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId} --winner ${matchData.fighter1}`);
-      console.log(`OR: npm run end-match -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId} --winner ${matchData.fighter2 || 'Fighter2'}`);
+      console.log(`End match: npm run end-match -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId} --winner <${matchData.fighters.map(pool => pool.name).join('|')}>`);
     } else if (status === 'completed') {
       console.log(`Claim prize: npm run claim-prize -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId}`);
       console.log(`Close match: npm run close-match -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId}`);
-    } else if (status === 'refund') {
+    } else if (status === 'refund' || status === 'draw' || status === 'noContest') {
       console.log(`Claim refund: npm run claim-refund -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId}`);
       console.log(`Close match: npm run close-match -- --matchaccount ${matchAccount.toString()} --matchid ${matchData.matchId}`);
     }
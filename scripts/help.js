@@ -13,6 +13,12 @@ Steps to use this program:
    $ npm run deploy     - Deploy program to local Solana network
    $ npm run initialize - Initialize program (setup house wallet)
 
+   # Upgrading an older deployment: migrate the house wallet once
+   $ npm run migrate-house-wallet -- --treasury <TREASURY_PUBLIC_KEY>
+
+   # Then pay out or refund each match created before the upgrade
+   $ npm run settle-legacy-match -- --matchid <LEGACY_MATCH_ID>
+
 2. Create Match:
    # Create match with default values (MATCH_001, DOGE vs SHIB)
    $ npm run create-match
@@ -20,6 +26,18 @@ Steps to use this program:
    # Create match with custom values
    $ npm run create-match -- --matchid MATCH_002 --fighter1 BTC --fighter2 ETH
 
+   # Create a battle royale with up to 16 fighters
+   $ npm run create-match -- --matchid MATCH_004 --fighters DOGE,SHIB,PEPE,BONK,WIF,FLOKI,MOG,POPCAT
+
+   # Pay the podium instead of winner-takes-all (before any bets are placed)
+   $ npm run set-payout-split -- --matchid MATCH_004 --split 70,20,10
+
+   # Cap single bets, per-wallet stakes and per-fighter pools (in SOL)
+   $ npm run set-bet-limits -- --matchid MATCH_004 --maxbet 5 --maxwallet 10 --maxpool 100
+
+   # Create a match that takes bets in an SPL Token or Token-2022 mint
+   $ npm run create-match -- --matchid MATCH_003 --mint <MINT_PUBLIC_KEY> --minbet 1000000
+
    # Show help for create-match options
    $ npm run create-match -- --help
 
@@ -30,6 +48,13 @@ Steps to use this program:
    # Show help for place-bet options
    $ npm run place-bet -- --help
 
+   # Betting again on the same fighter tops up your bet
+   $ npm run place-bet -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --fighter DOGE --amount 0.2
+
+   # Before betting closes, decrease a bet or cancel it (less any cancellation fee)
+   $ npm run cancel-bet -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --amount 0.05
+   $ npm run cancel-bet -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY>
+
 4. Update Match Status:
    # Update match status to Battle
    $ npm run update-status -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY>
@@ -40,13 +65,35 @@ Steps to use this program:
    # Show help for update-status options
    $ npm run update-status -- --help
 
+   # Anyone can lock betting once the match's betting close time has passed
+   $ npm run start-battle -- --matchid MATCH_001
+
+   # Anyone can refund a match that was not ended before its resolution deadline
+   $ npm run expire-match -- --matchid MATCH_001
+
 5. End Match:
    # End match with DOGE as winner
    $ npm run end-match -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --winner DOGE
 
+   # End a podium match with fighters in finishing order
+   $ npm run end-match -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --placements PEPE,DOGE,WIF
+
+   # End match as a draw (or --result nocontest); stakes become refundable
+   $ npm run end-match -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --result draw
+
    # Show help for end-match options
    $ npm run end-match -- --help
 
+   # Run a knockout tournament: seed the bracket, then create each bracket match
+   $ npm run create-tournament -- --tournamentid CUP_001 --fighters DOGE,SHIB,PEPE,WIF
+   $ npm run create-tournament-match -- --tournamentid CUP_001 --slot 1 --matchid CUP_001_M1
+
+   # Ending a bracket match advances its winner; after the final, anyone settles the winner pool
+   $ npm run settle-tournament-pool -- --tournamentid CUP_001
+
+   # If a bracket match was refunded instead of ended, a resolver advances a fighter from it
+   $ npm run advance-refunded-match -- --matchid CUP_001_M1 --winner DOGE
+
 6. Claim Prize:
    # Distribute prizes to all winners of a match
    $ npm run claim-prize -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001
@@ -54,9 +101,18 @@ Steps to use this program:
    # Distribute prizes to specific winners only (comma-separated list)
    $ npm run claim-prize -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --winners <PUBLIC_KEY_1>,<PUBLIC_KEY_2>
 
+   # Pay every targeted winner or fail the whole transaction
+   $ npm run claim-prize -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001 --strict
+
+   # Pay in smaller pages; each page advances the match's settled cursor
+   $ npm run claim-prize -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001 --pagesize 5
+
    # Show help for claim-prize options
    $ npm run claim-prize -- --help
 
+   # Bettors can also claim their own winnings without the authority
+   $ npm run claim-winnings -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001 --keypair <PATH_TO_BETTOR_KEYPAIR>
+
 7. Claim Refund:
    # Process refunds for all bettors of a match
    $ npm run claim-refund -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001
@@ -64,9 +120,15 @@ Steps to use this program:
    # Process refunds for specific bettors only (comma-separated list)
    $ npm run claim-refund -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --bettors <PUBLIC_KEY_1>,<PUBLIC_KEY_2>
 
+   # Refund every targeted bettor or fail the whole transaction
+   $ npm run claim-refund -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001 --strict
+
    # Show help for claim-refund options
    $ npm run claim-refund -- --help
 
+   # Bettors can also claim their own refund without the authority
+   $ npm run claim-bet-refund -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001 --keypair <PATH_TO_BETTOR_KEYPAIR>
+
 8. Set Pause State:
    # Pause the program
    $ npm run set-pause-state -- --paused true
@@ -77,6 +139,29 @@ Steps to use this program:
    # Show help for set-pause-state options
    $ npm run set-pause-state -- --help
 
+   # Change the fee (in basis points), fee cap and bet limits (in SOL)
+   $ npm run update-config -- --feebps 300 --maxfee 10 --minbet 0.05 --maxbet 100
+
+   # Keep the fee on draws and no contests instead of refunding in full
+   $ npm run update-config -- --drawhandling refundMinusFee
+
+   # Keep a fee (in basis points) when bettors cancel or decrease a bet
+   $ npm run update-config -- --cancelfeebps 100
+
+   # Let wallets back more than one fighter in new matches
+   $ npm run update-config -- --hedging allowHedging
+
+   # Change the treasury that receives fees for newly created matches
+   $ npm run set-treasury -- --treasury <TREASURY_PUBLIC_KEY>
+
+   # Hand the program authority to a new key (two steps)
+   $ npm run transfer-authority -- --propose <NEW_AUTHORITY_PUBLIC_KEY>
+   $ npm run transfer-authority -- --accept --keypair <PATH_TO_NEW_AUTHORITY_KEYPAIR>
+
+   # Grant or revoke a role (operator, resolver, treasurer, pauser)
+   $ npm run manage-roles -- --role operator --add <PUBLIC_KEY>
+   $ npm run manage-roles -- --role operator --remove <PUBLIC_KEY>
+
 9. Emergency Operations:
    # Perform emergency refund for a match
    $ npm run emergency-refund -- --matchaccount <MATCH_ACCOUNT_PUBLIC_KEY> --matchid MATCH_001
@@ -115,6 +200,11 @@ create-match:
   --matchid, -m    Match ID                  [string] [default: "MATCH_001"]
   --fighter1, -f1  First fighter name        [string] [default: "DOGE"]
   --fighter2, -f2  Second fighter name       [string] [default: "SHIB"]
+  --fighters       Comma-separated fighters  [string] (2-16, overrides -f1/-f2)
+  --opens          Betting opens (unix time) [number] [default: now]
+  --closes         Betting closes (unix time) [number] [default: opens + 1h]
+  --ends           Expected end (unix time)  [number] [default: closes + 1h]
+  --deadline       Resolution deadline       [number] [default: ends + 24h]
 
 place-bet:
   --matchaccount, -a  Match account public key    [string] [required]
@@ -122,25 +212,81 @@ place-bet:
   --fighter, -f       Fighter to bet on           [string] [default: "DOGE"]
   --amount, -amt      Bet amount in SOL           [number] [default: 0.1]
   --keypair, -k       Path to keypair file (bettor) [string]
+  --tokenaccount, -t  Token account to bet from (token matches) [string]
+
+cancel-bet:
+  --matchaccount, -a  Match account public key    [string] [required]
+  --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
+  --amount, -amt      Amount to take off; omit to cancel [number]
+  --fighter, -f       Fighter of a hedge bet      [string]
+  --tokenaccount      Bettor token account (token matches) [string] [default: associated token account]
+  --treasurytokenaccount  Treasury token account (token matches with a fee) [string] [default: associated token account]
+  --keypair, -k       Path to keypair file (bettor) [string]
 
 update-status:
   --matchaccount, -a  Match account public key    [string] [required]
   --status, -s        New match status            [string] [choices: "Initialized", "Battle", "Completed"] [default: "Battle"]
 
+set-payout-split:
+  --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
+  --split, -s         Percent per place, e.g. 70,20,10 [string] [required]
+
+set-bet-limits:
+  --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
+  --minbet            Minimum single bet          [number]
+  --maxbet            Maximum single bet          [number]
+  --maxwallet         Maximum stake per wallet    [number]
+  --maxpool           Maximum pool per fighter    [number]
+
+start-battle:
+  --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
+  --keypair, -k       Keypair paying for the tx   [string]
+
+expire-match:
+  --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
+  --keypair, -k       Keypair paying for the tx   [string]
+
 end-match:
   --matchaccount, -a  Match account public key    [string] [required]
   --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
   --winner, -w        Winner fighter name         [string] [default: "DOGE"]
+  --placements, -p    Fighters in finishing order [string]
+  --result, -r        End without a winner        [string] [choices: "draw", "nocontest"]
+  --treasurytokenaccount  Treasury token account for the fee (token matches) [string] [default: associated token account]
+
+create-tournament:
+  --tournamentid, -t  Tournament ID               [string] [default: "CUP_001"]
+  --poolid, -p        Winner pool match ID        [string] [default: "<TOURNAMENT_ID>_WIN"]
+  --fighters, -f      Fighters in seeding order   [string] [required]
+  --opens, --closes, --ends, --deadline  Winner pool schedule (unix seconds)
+
+create-tournament-match:
+  --tournamentid, -t  Tournament ID               [string] [default: "CUP_001"]
+  --slot, -s          Bracket slot (0 = final)    [number] [required]
+  --matchid, -m       Match ID                    [string] [required]
+  --opens, --closes, --ends, --deadline  Match schedule (unix seconds)
+
+settle-tournament-pool:
+  --tournamentid, -t  Tournament ID               [string] [default: "CUP_001"]
+  --keypair, -k       Keypair paying for the tx   [string]
+
+advance-refunded-match:
+  --matchid, -m       Refunded bracket match ID   [string] [required]
+  --winner, -w        Fighter that goes through   [string] [required]
 
 claim-prize:
   --matchaccount, -a  Match account public key    [string] [required]
   --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
   --winners, -w       Comma-separated list of winner public keys [string] [default: ""]
+  --pagesize          Bets to pay per transaction  [number] [default: 10]
+  --strict            Fail if any targeted bet cannot be paid [boolean] [default: false]
 
 claim-refund:
   --matchaccount, -a  Match account public key    [string] [required]
   --matchid, -m       Match ID                    [string] [default: "MATCH_001"]
   --bettors, -b       Comma-separated list of bettor public keys [string] [default: ""]
+  --pagesize          Bets to pay per transaction  [number] [default: 10]
+  --strict            Fail if any targeted bet cannot be paid [boolean] [default: false]
 
 set-pause-state:
   --paused, -p        Set program to paused state  [boolean] [default: true]
@@ -152,15 +298,21 @@ emergency-refund:
   --matchaccount, -a  Match account public key    [string] [required]
   --matchid, -m       Match ID                    [string] [required]
   --keypair, -k       Path to keypair file (authority) [string]
+  --pagesize          Bets to pay per transaction  [number] [default: 10]
+  --strict            Fail if any targeted bet cannot be paid [boolean] [default: false]
 
 close-match:
   --matchaccount, -a  Match account public key    [string] [required]
   --matchid, -m       Match ID                    [string] [required]
+  --pagesize          Bets to walk per transaction [number] [default: 10]
+  --treasurytokenaccount  Treasury token account for leftover tokens (token matches) [string] [default: associated token account]
 
 reclaim-prize:
   --matchaccount, -a  Match account public key    [string] [required]
   --matchid, -m       Match ID                    [string] [required]
   --bettor, -b        Public key of the bettor to reclaim prize for [string] [required]
+  --fighter, -f       Fighter of a hedge bet      [string]
+  --tokenaccount, -t  Bettor token account to pay into (token matches) [string] [default: associated token account]
   --keypair, -k       Path to keypair file (authority) [string]
 
 house-wallet-transfer-cpi:
@@ -174,6 +326,10 @@ get-match:
   --matchaccount, -a  Match account public key    [string] [required]
   --keypair, -k       Path to keypair file (authority) [string]
 
+listen-events:
+  --events, -e        Events to follow, e.g. betPlaced,matchEnded [string]
+  --matchid, -m       Only show events for this match [string]
+
 Notes:
 - Ensure wallet has enough SOL (via 'npm run setup')
 - Program runs on local Solana network
@@ -154,6 +154,9 @@ async function main() {
       console.log(`- Amount transferred: ${(houseBalance - newHouseBalance) / 1_000_000_000} SOL`);
     } catch (error) {
       console.error("Error sending transaction:", error);
+      if (error.toString().includes("WithdrawalExceedsFreeBalance")) {
+        console.error("Only free house profit can be withdrawn. Escrowed bets, unclaimed payouts and rent are locked.");
+      }
       process.exit(1);
     }
     
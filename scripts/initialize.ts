@@ -19,6 +19,11 @@ const argv = yargs(hideBin(process.argv))
     description: 'Path to keypair file (authority)',
     type: 'string'
   })
+  .option('treasury', {
+    alias: 't',
+    description: 'Treasury public key that receives match fees (defaults to the authority)',
+    type: 'string'
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -91,11 +96,16 @@ async function main() {
     program.programId
   );
   
+  const treasury = argv.treasury
+    ? new PublicKey(argv.treasury)
+    : provider.wallet.publicKey;
+  
   console.log("House wallet PDA:", houseWallet.toString());
+  console.log("Treasury:", treasury.toString());
   
   // Initialize house wallet
   const tx = await program.methods
-    .initialize(bump)
+    .initialize(bump, treasury)
     .accounts({
       houseWallet: houseWallet,
       authority: provider.wallet.publicKey,
@@ -0,0 +1,116 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+const EVENTS = [
+  'matchCreated',
+  'betPlaced',
+  'betWithdrawn',
+  'statusChanged',
+  'matchEnded',
+  'prizePaid',
+  'refundPaid',
+  'remainderPaid',
+  'houseWithdrawal',
+  'pauseChanged',
+  'authorityProposed',
+  'authorityTransferred',
+  'roleChanged',
+  'configUpdated',
+  'treasuryChanged',
+  'payoutSplitChanged',
+  'betLimitsChanged',
+  'bracketAdvanced',
+  'betClosed',
+  'matchClosed',
+  'houseWalletMigrated',
+  'legacyMatchSettled',
+];
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('events', {
+    alias: 'e',
+    description: `Comma-separated events to follow (default: all of ${EVENTS.join(', ')})`,
+    type: 'string'
+  })
+  .option('matchid', {
+    alias: 'm',
+    description: 'Only show events for this match ID',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+function format(value: any): any {
+  if (value instanceof anchor.BN) {
+    return value.toString();
+  }
+  if (value instanceof anchor.web3.PublicKey) {
+    return value.toBase58();
+  }
+  if (Array.isArray(value)) {
+    return value.map(format);
+  }
+  if (value && typeof value === 'object') {
+    return Object.fromEntries(Object.entries(value).map(([key, inner]) => [key, format(inner)]));
+  }
+  return value;
+}
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+  
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+  
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+  
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+  
+  const events = argv.events ? argv.events.split(',').map(name => name.trim()) : EVENTS;
+  const unknown = events.find(name => !EVENTS.includes(name));
+  if (unknown) {
+    console.error(`Error: Unknown event "${unknown}". Valid options are: ${EVENTS.join(', ')}`);
+    process.exit(1);
+  }
+  
+  for (const name of events) {
+    program.addEventListener(name as any, (event: any, slot: number, signature: string) => {
+      if (argv.matchid && event.matchId !== undefined && event.matchId !== argv.matchid) {
+        return;
+      }
+      console.log(JSON.stringify({ event: name, slot, signature, ...format(event) }));
+    });
+  }
+  
+  console.log(`Listening for ${events.join(', ')} on ${process.env.ANCHOR_PROVIDER_URL}. Press Ctrl+C to stop.`);
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,124 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+const ROLES = ['operator', 'resolver', 'treasurer', 'pauser'];
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('role', {
+    alias: 'r',
+    description: 'Role to manage',
+    choices: ROLES,
+    demandOption: true
+  })
+  .option('add', {
+    alias: 'a',
+    description: 'Public key to grant the role to',
+    type: 'string'
+  })
+  .option('remove', {
+    alias: 'd',
+    description: 'Public key to revoke the role from',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'arena-authority.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDA for house wallet
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    const role = argv.role as string;
+    const roleName = role.charAt(0).toUpperCase() + role.slice(1);
+
+    if (argv.add || argv.remove) {
+      const key = argv.add || argv.remove;
+      let member;
+      try {
+        member = new PublicKey(key);
+      } catch (e) {
+        console.error(`Error: Invalid public key format: ${key}`);
+        process.exit(1);
+      }
+
+      const method = argv.add ? `add${roleName}` : `remove${roleName}`;
+      console.log(`${argv.add ? 'Granting' : 'Revoking'} ${role} role ${argv.add ? 'to' : 'from'} ${member.toString()}...`);
+
+      const tx = await program.methods[method](member)
+        .accounts({
+          houseWallet: houseWallet,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      console.log("Transaction signature:", tx);
+    }
+
+    // Show the current members of the role
+    const houseInfo = await provider.connection.getAccountInfo(houseWallet);
+    const houseData = program.coder.accounts.decode('houseWallet', houseInfo.data);
+    const members = houseData[`${role}s`];
+    console.log(`\nCurrent ${role}s (${members.length}):`);
+    for (const member of members) {
+      console.log(`- ${member.toString()}`);
+    }
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("Unauthorized")) {
+      console.error("Only the program authority can manage roles.");
+    } else if (error.toString().includes("RoleFull")) {
+      console.error("This role already has the maximum number of members. Remove one first.");
+    } else if (error.toString().includes("RoleMemberExists")) {
+      console.error("This key already holds the role.");
+    } else if (error.toString().includes("RoleMemberNotFound")) {
+      console.error("This key does not hold the role.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,97 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('treasury', {
+    alias: 't',
+    description: 'Treasury public key that receives match fees once migrated',
+    type: 'string',
+    demandOption: true
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'arena-authority.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDA for house wallet
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    // Parse treasury public key
+    let treasury;
+    try {
+      treasury = new PublicKey(argv.treasury);
+    } catch (e) {
+      console.error(`Error: Invalid treasury public key format: ${argv.treasury}`);
+      process.exit(1);
+    }
+
+    console.log(`Migrating house wallet ${houseWallet.toString()} to the current layout...`);
+
+    const tx = await program.methods
+      .migrateHouseWallet(treasury)
+      .accounts({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    console.log("House wallet migrated with the default fee, bet limits and an empty role list.");
+    console.log("Use update-config and manage-roles to change them.");
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("Unauthorized")) {
+      console.error("You are not authorized to migrate the house wallet. Only the program authority can do this.");
+    } else if (error.toString().includes("AlreadyMigrated")) {
+      console.error("The house wallet already uses the current layout.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
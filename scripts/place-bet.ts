@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -34,7 +35,7 @@ const argv = yargs(hideBin(process.argv))
   })
   .option('amount', {
     alias: 'amt',
-    description: 'Bet amount in SOL',
+    description: 'Bet amount in SOL, or in base units of the mint for token matches',
     type: 'number',
     default: 0.1
   })
@@ -43,6 +44,11 @@ const argv = yargs(hideBin(process.argv))
     description: 'Path to keypair file',
     type: 'string'
   })
+  .option('tokenaccount', {
+    alias: 't',
+    description: 'Token account to bet from (token matches only)',
+    type: 'string'
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -103,11 +109,11 @@ async function main() {
     process.exit(1);
   }
   
-  // Check wallet balance
+  // Check wallet balance (token bets are checked by the token program)
   const walletBalance = await provider.connection.getBalance(provider.wallet.publicKey);
   const requiredBalance = argv.amount * LAMPORTS_PER_SOL;
   
-  if (walletBalance < requiredBalance) {
+  if (!argv.tokenaccount && walletBalance < requiredBalance) {
     console.error(`Error: Insufficient SOL balance. You have ${walletBalance / LAMPORTS_PER_SOL} SOL but need ${argv.amount} SOL`);
     console.log(`To add more SOL: solana airdrop 1 $(solana address -k ${walletPath})`);
     process.exit(1);
@@ -163,21 +169,21 @@ async function main() {
     }
     
     // Verify the fighter is valid
-    const validFighters = [];
-    validFighters.push(matchData.fighter1);
-    
-    // Fighter 2 might be empty if this is the first bet
-    if (matchData.fighter2 && matchData.fighter2.trim() !== '') {
-      validFighters.push(matchData.fighter2);
+    const validFighters = matchData.fighters.map(pool => pool.name);
+    if (!validFighters.includes(argv.fighter)) {
+      console.error(`Error: Invalid fighter "${argv.fighter}". Valid options are: ${validFighters.join(', ')}`);
+      console.log(`Tip: Use --fighter ${validFighters[0]}`);
+      process.exit(1);
     }
     
-    // Check if fighter 2 is empty but the bet is not for fighter 1
-    if (matchData.fighter2 === '' && argv.fighter !== matchData.fighter1) {
-      // This will be the second fighter
-      console.log(`Setting ${argv.fighter} as the second fighter.`);
-    } else if (!validFighters.includes(argv.fighter)) {
-      console.error(`Error: Invalid fighter "${argv.fighter}". Valid options are: ${validFighters.join(', ')}`);
-      console.log(`Tip: Use --fighter ${validFighters[0]}${validFighters.length > 1 ? ` or --fighter ${validFighters[1]}` : ''}`);
+    // Check the betting window
+    const now = Math.floor(Date.now() / 1000);
+    if (now < matchData.bettingOpensAt.toNumber()) {
+      console.error(`Error: Betting opens at ${new Date(matchData.bettingOpensAt.toNumber() * 1000).toISOString()}`);
+      process.exit(1);
+    }
+    if (now >= matchData.bettingClosesAt.toNumber()) {
+      console.error(`Error: Betting closed at ${new Date(matchData.bettingClosesAt.toNumber() * 1000).toISOString()}`);
       process.exit(1);
     }
     
@@ -188,24 +194,67 @@ async function main() {
       process.exit(1);
     }
     
-    // Calculate bet amount in lamports
-    const amount = new anchor.BN(argv.amount * LAMPORTS_PER_SOL);
+    // Calculate bet amount in lamports, or base units for token matches
+    const amount = matchData.betMint
+      ? new anchor.BN(argv.amount)
+      : new anchor.BN(argv.amount * LAMPORTS_PER_SOL);
+
+    // Token matches move tokens from the bettor's token account into the match vault
+    if (matchData.betMint && !argv.tokenaccount) {
+      console.error(`Error: This match takes bets in ${matchData.betMint.toString()}. Pass --tokenaccount <TOKEN_ACCOUNT>.`);
+      process.exit(1);
+    }
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const tokenAccounts = matchTokens
+      ? { ...matchTokens, bettorTokenAccount: new PublicKey(argv.tokenaccount) }
+      : {};
+    
+    // Repeat bets on the same fighter top up the existing bet; other fighters need a hedge bet
+    const [betAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("bet"), matchAccount.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const existingBet = await program.account.bet.fetchNullable(betAccount);
+    let hedgeBet: PublicKey | null = null;
+    if (existingBet && existingBet.fighter !== argv.fighter) {
+      if (!matchData.hedgingPolicy.allowHedging) {
+        console.error(`Error: You already backed ${existingBet.fighter} and this match does not allow hedging.`);
+        process.exit(1);
+      }
+      [hedgeBet] = await PublicKey.findProgramAddress(
+        [Buffer.from("bet"), matchAccount.toBuffer(), provider.wallet.publicKey.toBuffer(), Buffer.from(argv.fighter)],
+        program.programId
+      );
+    }
     
     console.log(`Match data:`);
     console.log(`- Match ID: ${matchData.matchId}`);
-    console.log(`- Fighter 1: ${matchData.fighter1}`);
-    console.log(`- Fighter 2: ${matchData.fighter2}`);
+    console.log(`- Fighters: ${validFighters.join(', ')}`);
     console.log(`- Status: ${currentStatus}`);
+    if (existingBet && !hedgeBet) {
+      console.log(`- Existing bet: ${existingBet.amount.toString()} on ${existingBet.fighter} (topping up)`);
+    } else if (hedgeBet) {
+      console.log(`- Existing bet: ${existingBet.amount.toString()} on ${existingBet.fighter} (hedging)`);
+    }
     console.log(`\nPlacing bet of ${argv.amount} SOL on ${argv.fighter} for match ${argv.matchid}`);
     
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
     // Place bet
     const tx = await program.methods
       .placeBet(argv.matchid, argv.fighter, amount)
       .accounts({
         matchAccount: matchAccount,
         houseWallet: houseWallet,
+        escrow: matchData.betMint ? null : escrow,
         bettor: provider.wallet.publicKey,
+        hedgeBet: hedgeBet,
         systemProgram: anchor.web3.SystemProgram.programId,
+        ...tokenAccounts,
       })
       .rpc();
     
@@ -218,7 +267,19 @@ async function main() {
     console.error("Error:", error);
     
     // Provide more helpful error messages based on common error cases
-    if (error.toString().includes("InvalidFighter")) {
+    if (error.toString().includes("BetTooSmall")) {
+      console.error("The bet is under this match's minimum. For Token-2022 mints, the minimum applies after transfer fees.");
+    } else if (error.toString().includes("BetTooLarge")) {
+      console.error("The bet is over this match's maximum single bet.");
+    } else if (error.toString().includes("WalletLimitExceeded")) {
+      console.error("The bet would take your total stake in this match over its per-wallet limit.");
+    } else if (error.toString().includes("FighterPoolLimitExceeded")) {
+      console.error("The bet would take this fighter's pool over the match's limit.");
+    } else if (error.toString().includes("AlreadyClaimed")) {
+      console.error("This bet has already been refunded and cannot be added to.");
+    } else if (error.toString().includes("HedgingNotAllowed")) {
+      console.error("This match only lets you add to the fighter you already backed.");
+    } else if (error.toString().includes("InvalidFighter")) {
       console.error("Invalid fighter. Make sure the fighter name is one of the fighters in this match.");
     } else if (error.toString().includes("InvalidMatchId")) {
       console.error("Invalid match ID. Make sure the match ID matches what was used when creating the match.");
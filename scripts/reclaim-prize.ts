@@ -7,6 +7,7 @@ import * as os from 'os';
 import yargs from 'yargs';
 import { hideBin } from 'yargs/helpers';
 import idl from "../target/idl/battle_memecoin_club.json";
+import { matchTokenAccounts, payoutTokenAccount } from "./token-accounts";
 import dotenv from 'dotenv';
 
 // Load environment variables
@@ -26,12 +27,22 @@ const argv = yargs(hideBin(process.argv))
     type: 'string',
     default: 'MATCH_001'
   })
+  .option('fighter', {
+    alias: 'f',
+    description: 'Fighter of a hedge bet; omit for the wallet\'s first bet in the match',
+    type: 'string'
+  })
   .option('bettor', {
     alias: 'b',
     description: 'Public key of the bettor to reclaim prize for',
     type: 'string',
     demandOption: true
   })
+  .option('tokenaccount', {
+    alias: 't',
+    description: 'Bettor's token account to pay into (token matches only; default: the associated token account)',
+    type: 'string'
+  })
   .help()
   .alias('help', 'h')
   .parse();
@@ -131,41 +142,65 @@ async function main() {
     console.log(`Match winner is: ${matchData.winner}`);
     console.log(`Bettor: ${bettorPubkey.toString()}`);
     
-    // Find the specific bettor in the bet list
-    const bettorBet = matchData.bets.find(bet => 
-      !bet.claimed && 
-      bet.fighter === matchData.winner && 
-      bet.bettor.toString() === bettorPubkey.toString()
+    // Find the bettor's bet account for this match
+    let [betAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("bet"), matchAccount.toBuffer(), bettorPubkey.toBuffer()],
+      program.programId
     );
+
+    // A hedge bet on another fighter lives at its own address
+    if (argv.fighter) {
+      const mainBet = await program.account.bet.fetchNullable(betAccount);
+      if (!mainBet || mainBet.fighter !== argv.fighter) {
+        [betAccount] = await PublicKey.findProgramAddress(
+          [Buffer.from("bet"), matchAccount.toBuffer(), bettorPubkey.toBuffer(), Buffer.from(argv.fighter)],
+          program.programId
+        );
+      }
+    }
+    const betInfo = await provider.connection.getAccountInfo(betAccount);
+    const bet = betInfo ? program.coder.accounts.decode('bet', betInfo.data) : null;
+    const placedFighters = matchData.placements.map(placement => placement.fighter);
+    const bettorBet = bet && !bet.claimed && placedFighters.includes(bet.fighter) ? bet : null;
     
     if (!bettorBet) {
       console.error(`Error: Bettor ${bettorPubkey.toString()} either does not have an unclaimed bet or did not bet on the winning fighter.`);
       process.exit(1);
     }
     
-    const betAmount = bettorBet.amount / anchor.web3.LAMPORTS_PER_SOL;
-    console.log(`Bettor has an unclaimed bet of ${betAmount} SOL on ${bettorBet.fighter}`);
-    
-    // Add bettor to remaining accounts
-    const remainingAccounts = [{
-      pubkey: bettorPubkey,
-      isWritable: true,
-      isSigner: false
-    }];
+    const betAmount = matchData.betMint
+      ? `${bettorBet.amount.toString()} base units`
+      : `${bettorBet.amount / anchor.web3.LAMPORTS_PER_SOL} SOL`;
+    console.log(`Bettor has an unclaimed bet of ${betAmount} on ${bettorBet.fighter}`);
     
     // Execute reclaim prize transaction
     console.log(`\nReclaiming prize for bettor ${bettorPubkey.toString()}...`);
     
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), matchAccount.toBuffer()],
+      program.programId
+    );
+
+    // Token matches pay out of the vault into the bettor's token account
+    const matchTokens = await matchTokenAccounts(provider.connection, program.programId, matchAccount, matchData);
+    const bettorTokenAccount = matchTokens
+      ? payoutTokenAccount(matchTokens, bettorPubkey, argv.tokenaccount)
+      : null;
+    const tokenAccounts = matchTokens ? { ...matchTokens, bettorTokenAccount } : {};
+
     const tx = await program.methods
       .reclaimPrize(argv.matchid, bettorPubkey)
       .accounts({
         matchAccount: matchAccount,
+        bet: betAccount,
         houseWallet: houseWallet,
-        treasury: process.env.TREASURY_WALLET,
+        escrow: matchData.betMint ? null : escrow,
+        bettor: bettorPubkey,
         authority: provider.wallet.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
+        ...tokenAccounts,
       })
-      .remainingAccounts(remainingAccounts)
       .rpc();
     
     console.log("Transaction signature:", tx);
@@ -0,0 +1,142 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID',
+    type: 'string',
+    default: 'MATCH_001'
+  })
+  .option('minbet', {
+    description: 'Minimum single bet (SOL, or base units for token matches)',
+    type: 'number'
+  })
+  .option('maxbet', {
+    description: 'Maximum single bet (SOL, or base units for token matches)',
+    type: 'number'
+  })
+  .option('maxwallet', {
+    description: 'Maximum total stake per wallet across all its bets (SOL, or base units for token matches)',
+    type: 'number'
+  })
+  .option('maxpool', {
+    description: 'Maximum total staked on any one fighter (SOL, or base units for token matches)',
+    type: 'number'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDAs for match and house wallet
+    const [matchAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(argv.matchid)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    // Fetch match data
+    const accountInfo = await provider.connection.getAccountInfo(matchAccount);
+    if (!accountInfo) {
+      console.error(`Error: Match account not found at ${matchAccount.toString()}`);
+      process.exit(1);
+    }
+
+    const matchData = program.coder.accounts.decode('matchAccount', accountInfo.data);
+
+    // Keep current values for anything not specified on the command line
+    const toUnits = (value: number | undefined, current: anchor.BN) => {
+      if (value === undefined) {
+        return current;
+      }
+      return matchData.betMint
+        ? new anchor.BN(value)
+        : new anchor.BN(Math.round(value * LAMPORTS_PER_SOL));
+    };
+    const minBet = toUnits(argv.minbet, matchData.minBet);
+    const maxBet = toUnits(argv.maxbet, matchData.maxBet);
+    const maxWalletTotal = toUnits(argv.maxwallet, matchData.maxWalletTotal);
+    const maxFighterPool = toUnits(argv.maxpool, matchData.maxFighterPool);
+
+    if (minBet.isZero() || minBet.gt(maxBet) || maxBet.gt(maxWalletTotal)) {
+      console.error('Error: Limits must satisfy 0 < min bet <= max bet <= max per wallet');
+      process.exit(1);
+    }
+
+    const unit = matchData.betMint ? 'base units' : 'lamports';
+    console.log(`Setting bet limits for match ${argv.matchid}:`);
+    console.log(`- Min bet: ${minBet.toString()} ${unit}`);
+    console.log(`- Max bet: ${maxBet.toString()} ${unit}`);
+    console.log(`- Max per wallet: ${maxWalletTotal.toString()} ${unit}`);
+    console.log(`- Max per fighter: ${maxFighterPool.toString()} ${unit}`);
+
+    const tx = await program.methods
+      .setBetLimits(argv.matchid, minBet, maxBet, maxWalletTotal, maxFighterPool)
+      .accounts({
+        matchAccount: matchAccount,
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    console.log("Bet limits updated. They apply to bets placed from now on.");
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("InvalidBetLimits")) {
+      console.error("Limits must satisfy 0 < min bet <= max bet <= max per wallet.");
+    } else if (error.toString().includes("MatchNotInPreparation")) {
+      console.error("Bet limits can only change while the match is taking bets.");
+    } else if (error.toString().includes("Unauthorized")) {
+      console.error("Only an operator can set bet limits.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,125 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID',
+    type: 'string',
+    default: 'MATCH_001'
+  })
+  .option('split', {
+    alias: 's',
+    description: 'Comma-separated percentages of the losing pool per place, e.g. 70,20,10',
+    type: 'string',
+    demandOption: true
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDAs for match and house wallet
+    const [matchAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(argv.matchid)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    // Fetch match data
+    const accountInfo = await provider.connection.getAccountInfo(matchAccount);
+    if (!accountInfo) {
+      console.error(`Error: Match account not found at ${matchAccount.toString()}`);
+      process.exit(1);
+    }
+
+    const matchData = program.coder.accounts.decode('matchAccount', accountInfo.data);
+
+    // Percentages are stored on-chain in basis points
+    const split = argv.split.split(',').map(percent => Math.round(parseFloat(percent) * 100));
+    if (split.some(bps => isNaN(bps) || bps <= 0)) {
+      console.error(`Error: Invalid split "${argv.split}"`);
+      process.exit(1);
+    }
+    if (split.reduce((total, bps) => total + bps, 0) !== 10000) {
+      console.error(`Error: The split must add up to 100%`);
+      process.exit(1);
+    }
+    if (split.length >= matchData.fighters.length) {
+      console.error(`Error: A ${matchData.fighters.length}-fighter match can pay at most ${matchData.fighters.length - 1} places`);
+      process.exit(1);
+    }
+
+    console.log(`Setting payout split for match ${argv.matchid} to ${split.map(bps => `${bps / 100}%`).join(' / ')}...`);
+
+    const tx = await program.methods
+      .setPayoutSplit(argv.matchid, split)
+      .accounts({
+        matchAccount: matchAccount,
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    console.log("Payout split updated. Bettors on each placed fighter share that place's cut of the losing pool.");
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("PayoutSplitLocked")) {
+      console.error("Bets have already been placed; the payout split can no longer change.");
+    } else if (error.toString().includes("TournamentPayoutFixed")) {
+      console.error("Tournament bracket matches and winner pools always pay a single winner.");
+    } else if (error.toString().includes("InvalidPayoutSplit")) {
+      console.error("The split needs 1-5 non-zero places, fewer than the fighters, adding up to 100%.");
+    } else if (error.toString().includes("Unauthorized")) {
+      console.error("Only an operator can set the payout split.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
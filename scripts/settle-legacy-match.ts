@@ -0,0 +1,174 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('matchid', {
+    alias: 'm',
+    description: 'Match ID',
+    type: 'string',
+    default: 'MATCH_001'
+  })
+  .option('pagesize', {
+    alias: 'p',
+    description: 'Bettors paid per transaction',
+    type: 'number',
+    default: 10
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+const LEGACY_STATUSES = ['Preparation', 'Battle', 'Completed', 'Refund'];
+
+// Legacy matches predate the IDL's MatchAccount, so their bets are read straight from the account data
+function decodeLegacyMatch(data: Buffer) {
+  let offset = 8;
+  const readString = () => {
+    const length = data.readUInt32LE(offset);
+    const value = data.subarray(offset + 4, offset + 4 + length).toString('utf-8');
+    offset += 4 + length;
+    return value;
+  };
+  const readU64 = () => {
+    const value = data.readBigUInt64LE(offset);
+    offset += 8;
+    return value;
+  };
+
+  const matchId = readString();
+  const fighter1 = readString();
+  const fighter2 = readString();
+  readU64();
+  readU64();
+  const status = LEGACY_STATUSES[data.readUInt8(offset++)];
+  const winner = data.readUInt8(offset++) === 1 ? readString() : null;
+  readU64();
+  const betCount = data.readUInt32LE(offset);
+  offset += 4;
+
+  const bets = [];
+  for (let i = 0; i < betCount; i++) {
+    const bettor = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+    const amount = readU64();
+    const fighter = readString();
+    const claimed = data.readUInt8(offset++) === 1;
+    bets.push({ bettor, amount, fighter, claimed });
+  }
+
+  return { matchId, fighter1, fighter2, status, winner, bets };
+}
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'arena-authority.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDAs for the match and house wallet
+    const [matchAccount] = await PublicKey.findProgramAddress(
+      [Buffer.from("match"), Buffer.from(argv.matchid)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    const accountInfo = await provider.connection.getAccountInfo(matchAccount);
+    if (!accountInfo) {
+      console.error(`Error: Match account not found at ${matchAccount.toString()}`);
+      process.exit(1);
+    }
+
+    const legacyMatch = decodeLegacyMatch(accountInfo.data);
+    console.log(`Legacy match ${legacyMatch.matchId}: ${legacyMatch.fighter1} vs ${legacyMatch.fighter2}, status ${legacyMatch.status}`);
+    if (legacyMatch.status === 'Completed') {
+      console.log(`Paying winners of ${legacyMatch.winner}; losing bets are settled without a payout.`);
+    } else {
+      console.log("The match was never completed, so every stake is refunded.");
+    }
+
+    const unclaimed = [...new Set(
+      legacyMatch.bets
+        .filter(bet => !bet.claimed)
+        .map(bet => bet.bettor.toString())
+    )];
+    console.log(`${unclaimed.length} unsettled bets`);
+
+    // Every call settles the bettors it is given; the last one closes the account
+    const pageSize = Math.max(1, argv.pagesize);
+    for (let start = 0; start === 0 || start < unclaimed.length; start += pageSize) {
+      const page = unclaimed.slice(start, start + pageSize);
+      const tx = await program.methods
+        .settleLegacyMatch(argv.matchid)
+        .accounts({
+          matchAccount: matchAccount,
+          houseWallet: houseWallet,
+          authority: provider.wallet.publicKey,
+        })
+        .remainingAccounts(page.map(bettor => ({
+          pubkey: new PublicKey(bettor),
+          isWritable: true,
+          isSigner: false,
+        })))
+        .rpc();
+      console.log(`Settled ${page.length} bettors. Transaction signature: ${tx}`);
+    }
+
+    const remaining = await provider.connection.getAccountInfo(matchAccount);
+    if (!remaining || remaining.data.length === 0) {
+      console.log("All bets settled and the legacy match account was closed.");
+    } else {
+      console.log("Some bets are still unsettled; run this command again to finish.");
+    }
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("Unauthorized")) {
+      console.error("You are not authorized to settle legacy matches. Only the program authority can do this.");
+    } else if (error.toString().includes("NotLegacyAccount")) {
+      console.error("This match already uses the current layout; use claim-prize or claim-refund instead.");
+    } else if (error.toString().includes("PayoutShortfall")) {
+      console.error("The house wallet does not hold enough SOL to pay this bettor.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,138 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('tournamentid', {
+    alias: 't',
+    description: 'Tournament ID',
+    type: 'string',
+    default: 'CUP_001'
+  })
+  .option('keypair', {
+    alias: 'k',
+    description: 'Path to the keypair paying for the transaction',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  let walletPath = path.join(os.homedir(), '.config', 'solana', 'authority-test.json');
+
+  // Use custom keypair if provided
+  if (argv.keypair) {
+    if (fs.existsSync(argv.keypair)) {
+      walletPath = argv.keypair;
+    } else {
+      console.log(`Keypair file not found at ${argv.keypair}`);
+      process.exit(1);
+    }
+  } else if (!fs.existsSync(walletPath)) {
+    console.log(`Wallet file not found at ${walletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = walletPath;
+
+  const wallet = new anchor.Wallet(
+    anchor.web3.Keypair.fromSecretKey(
+      new Uint8Array(JSON.parse(fs.readFileSync(walletPath, 'utf-8')))
+    )
+  );
+
+  const provider = new anchor.AnchorProvider(
+    new anchor.web3.Connection(process.env.ANCHOR_PROVIDER_URL),
+    wallet,
+    { commitment: 'confirmed' }
+  );
+
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDAs for the tournament and house wallet
+    const [tournament] = await PublicKey.findProgramAddress(
+      [Buffer.from("tournament"), Buffer.from(argv.tournamentid)],
+      program.programId
+    );
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    const tournamentData = await program.account.tournament.fetch(tournament);
+    if (!tournamentData.champion) {
+      console.error(`Error: Tournament ${argv.tournamentid} has no champion yet. End the final first.`);
+      process.exit(1);
+    }
+
+    const winnerPool = tournamentData.winnerPool;
+    const poolData = await program.account.matchAccount.fetch(winnerPool);
+
+    console.log(`Settling winner pool ${poolData.matchId} with champion ${tournamentData.champion}...`);
+
+    // SOL stakes are held in the match's own escrow PDA
+    const [escrow] = await PublicKey.findProgramAddress(
+      [Buffer.from("escrow"), winnerPool.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .settleTournamentPool(poolData.matchId)
+      .accounts({
+        matchAccount: winnerPool,
+        houseWallet: houseWallet,
+        escrow: poolData.betMint ? null : escrow,
+        treasury: poolData.treasury,
+        authority: provider.wallet.publicKey,
+        tournament: tournament,
+        nextMatch: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    console.log(`Winner pool settled with ${tournamentData.champion} as champion!`);
+    console.log(`\nNext steps:`);
+    console.log(`- To claim prizes: npm run claim-prize -- --matchaccount ${winnerPool.toString()} --matchid ${poolData.matchId}`);
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("TournamentNotFinished")) {
+      console.error("The tournament final has not ended yet.");
+    } else if (error.toString().includes("ResolutionDeadlinePassed")) {
+      console.error("The winner pool's resolution deadline has passed. Use 'npm run expire-match' to refund it.");
+    } else if (error.toString().includes("ProgramPaused")) {
+      console.error("The program is currently paused. Try again once it is unpaused.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
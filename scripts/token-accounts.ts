@@ -0,0 +1,46 @@
+import { Connection, PublicKey } from "@solana/web3.js";
+
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+// The associated token account an owner holds for a mint under the given token program
+export function associatedTokenAccount(owner: PublicKey, mint: PublicKey, tokenProgram: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  )[0];
+}
+
+// Mint, vault and token program accounts of a token match, or null for a SOL match
+export async function matchTokenAccounts(
+  connection: Connection,
+  programId: PublicKey,
+  matchAccount: PublicKey,
+  matchData: any
+): Promise<{ betMint: PublicKey; vault: PublicKey; tokenProgram: PublicKey } | null> {
+  if (!matchData.betMint) {
+    return null;
+  }
+
+  // The mint's owner tells us whether this is SPL Token or Token-2022
+  const mintInfo = await connection.getAccountInfo(matchData.betMint);
+  if (!mintInfo) {
+    throw new Error(`Mint not found at ${matchData.betMint.toString()}`);
+  }
+  const [vault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), matchAccount.toBuffer()],
+    programId
+  );
+
+  return { betMint: matchData.betMint, vault, tokenProgram: mintInfo.owner };
+}
+
+// Token account to pay an owner into: the one given on the command line, or their associated token account
+export function payoutTokenAccount(
+  tokenAccounts: { betMint: PublicKey; tokenProgram: PublicKey },
+  owner: PublicKey,
+  tokenAccount?: string
+): PublicKey {
+  return tokenAccount
+    ? new PublicKey(tokenAccount)
+    : associatedTokenAccount(owner, tokenAccounts.betMint, tokenAccounts.tokenProgram);
+}
@@ -0,0 +1,132 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('propose', {
+    alias: 'p',
+    description: 'Public key to propose as the new authority (signed by the current authority)',
+    type: 'string'
+  })
+  .option('accept', {
+    alias: 'a',
+    description: 'Accept a pending authority transfer (signed by the proposed authority)',
+    type: 'boolean',
+    default: false
+  })
+  .option('keypair', {
+    alias: 'k',
+    description: 'Path to keypair file',
+    type: 'string'
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  if (!argv.propose === !argv.accept) {
+    console.error('Error: Specify exactly one of --propose <PUBLIC_KEY> or --accept');
+    process.exit(1);
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  let walletPath = path.join(os.homedir(), '.config', 'solana', 'arena-authority.json');
+
+  // Use custom keypair if provided
+  if (argv.keypair) {
+    walletPath = argv.keypair;
+  }
+
+  // Check if wallet file exists
+  if (!fs.existsSync(walletPath)) {
+    console.log(`Wallet file not found at ${walletPath}.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = walletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDA for house wallet
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    let tx;
+    if (argv.propose) {
+      // Parse proposed authority public key
+      let newAuthority;
+      try {
+        newAuthority = new PublicKey(argv.propose);
+      } catch (e) {
+        console.error(`Error: Invalid public key format: ${argv.propose}`);
+        process.exit(1);
+      }
+
+      console.log(`Proposing ${newAuthority.toString()} as the new authority...`);
+
+      tx = await program.methods
+        .proposeAuthority(newAuthority)
+        .accounts({
+          houseWallet: houseWallet,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      console.log("Transaction signature:", tx);
+      console.log("Authority transfer proposed. The new authority must now run:");
+      console.log("npm run transfer-authority -- --accept --keypair <PATH_TO_NEW_AUTHORITY_KEYPAIR>");
+    } else {
+      console.log(`Accepting authority as ${provider.wallet.publicKey.toString()}...`);
+
+      tx = await program.methods
+        .acceptAuthority()
+        .accounts({
+          houseWallet: houseWallet,
+          newAuthority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      console.log("Transaction signature:", tx);
+      console.log("Authority transfer complete.");
+    }
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("Unauthorized")) {
+      console.error(argv.propose
+        ? "Only the current authority can propose a new authority."
+        : "This keypair is not the pending authority.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -0,0 +1,161 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import * as fs from 'fs';
+import * as path from 'path';
+import * as os from 'os';
+import yargs from 'yargs';
+import { hideBin } from 'yargs/helpers';
+import idl from "../target/idl/battle_memecoin_club.json";
+import dotenv from 'dotenv';
+
+// Load environment variables
+dotenv.config();
+
+// Parse command line arguments
+const argv = yargs(hideBin(process.argv))
+  .option('feebps', {
+    alias: 'f',
+    description: 'Fee on losing bets in basis points (500 = 5%)',
+    type: 'number'
+  })
+  .option('maxfee', {
+    alias: 'x',
+    description: 'Maximum fee per match in SOL',
+    type: 'number'
+  })
+  .option('minbet', {
+    alias: 'b',
+    description: 'Minimum bet in SOL',
+    type: 'number'
+  })
+  .option('maxbet', {
+    alias: 'm',
+    description: 'Maximum bet in SOL',
+    type: 'number'
+  })
+  .option('drawhandling', {
+    alias: 'd',
+    description: 'How draws and no contests are refunded',
+    type: 'string',
+    choices: ['fullRefund', 'refundMinusFee']
+  })
+  .option('cancelfeebps', {
+    alias: 'c',
+    description: 'Fee in basis points kept when a bettor cancels or decreases a bet',
+    type: 'number'
+  })
+  .option('hedging', {
+    description: 'Whether a wallet may back more than one fighter in a match',
+    type: 'string',
+    choices: ['sameFighterOnly', 'allowHedging']
+  })
+  .help()
+  .alias('help', 'h')
+  .parse();
+
+async function main() {
+  // ensure environment variables are set
+  if (!process.env.ANCHOR_PROVIDER_URL) {
+    process.env.ANCHOR_PROVIDER_URL = "http://localhost:8899";
+  }
+
+  // Set up wallet path using os.homedir() to avoid tilde (~) issues
+  const defaultWalletPath = path.join(os.homedir(), '.config', 'solana', 'arena-authority.json');
+
+  // Check if wallet file exists
+  if (!fs.existsSync(defaultWalletPath)) {
+    console.log(`Wallet file not found at ${defaultWalletPath}. Please run 'npm run setup' first.`);
+    process.exit(1);
+  }
+
+  // Set wallet path
+  process.env.ANCHOR_WALLET = defaultWalletPath;
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = new Program(idl as any, provider);
+
+  try {
+    // Find PDA for house wallet
+    const [houseWallet] = await PublicKey.findProgramAddress(
+      [Buffer.from("house")],
+      program.programId
+    );
+
+    const houseInfo = await provider.connection.getAccountInfo(houseWallet);
+    if (!houseInfo) {
+      console.error(`Error: House wallet not found at ${houseWallet.toString()}. Run 'npm run initialize' first.`);
+      process.exit(1);
+    }
+    const houseData = program.coder.accounts.decode('houseWallet', houseInfo.data);
+
+    // Keep current values for anything not specified on the command line
+    const feeBps = argv.feebps ?? houseData.feeBps;
+    const maxFee = argv.maxfee !== undefined
+      ? new anchor.BN(argv.maxfee * LAMPORTS_PER_SOL)
+      : houseData.maxFee;
+    const minBet = argv.minbet !== undefined
+      ? new anchor.BN(argv.minbet * LAMPORTS_PER_SOL)
+      : houseData.minBet;
+    const maxBet = argv.maxbet !== undefined
+      ? new anchor.BN(argv.maxbet * LAMPORTS_PER_SOL)
+      : houseData.maxBet;
+    const drawHandling = argv.drawhandling !== undefined
+      ? { [argv.drawhandling]: {} }
+      : houseData.drawHandling;
+    const cancelFeeBps = argv.cancelfeebps ?? houseData.cancelFeeBps;
+    const hedgingPolicy = argv.hedging !== undefined
+      ? { [argv.hedging]: {} }
+      : houseData.hedgingPolicy;
+
+    if (feeBps < 0 || feeBps > 10000) {
+      console.error('Error: Fee basis points must be between 0 and 10000');
+      process.exit(1);
+    }
+
+    if (cancelFeeBps < 0 || cancelFeeBps > 10000) {
+      console.error('Error: Cancellation fee basis points must be between 0 and 10000');
+      process.exit(1);
+    }
+
+    console.log(`Updating config:`);
+    console.log(`- Fee: ${feeBps} bps`);
+    console.log(`- Max fee: ${maxFee.toString()} lamports`);
+    console.log(`- Min bet: ${minBet.toString()} lamports`);
+    console.log(`- Max bet: ${maxBet.toString()} lamports`);
+    console.log(`- Draw handling: ${Object.keys(drawHandling)[0]}`);
+    console.log(`- Cancellation fee: ${cancelFeeBps} bps`);
+    console.log(`- Hedging: ${Object.keys(hedgingPolicy)[0]}`);
+
+    const tx = await program.methods
+      .updateConfig(feeBps, maxFee, minBet, maxBet, drawHandling, cancelFeeBps, hedgingPolicy)
+      .accounts({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("Transaction signature:", tx);
+    console.log("Config updated. Existing matches keep the terms they were created with.");
+  } catch (error) {
+    console.error("Error:", error);
+
+    // Provide more helpful error messages based on common error cases
+    if (error.toString().includes("Unauthorized")) {
+      console.error("You are not authorized to update the config. Only the program authority can do this.");
+    } else if (error.toString().includes("InvalidFeeBps")) {
+      console.error("Fee basis points cannot exceed 10000 (100%).");
+    } else if (error.toString().includes("InvalidBetLimits")) {
+      console.error("Minimum bet must be above zero and cannot exceed maximum bet.");
+    }
+
+    process.exit(1);
+  }
+}
+
+main().catch(err => {
+  console.error(err);
+  process.exit(1);
+});
@@ -87,8 +87,7 @@ async function main() {
     
     console.log(`Match data:`);
     console.log(`- Match ID: ${matchData.matchId}`);
-    console.log(`- Fighter 1: ${matchData.fighter1}`);
-    console.log(`- Fighter 2: ${matchData.fighter2}`);
+    console.log(`- Fighters: ${matchData.fighters.map(pool => pool.name).join(', ')}`);
     console.log(`- Current status: ${Object.keys(matchData.status)[0]}`);
     
     // Check if status update is valid - must be from preparation to battle per lib.rs
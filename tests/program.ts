@@ -1,22 +1,337 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { BattleMemecoinClub } from "../target/types/battle_memecoin_club";
-import { PublicKey, LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  burn,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { expect } from "chai";
 
 describe("battle_memecoin", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
-  const program = anchor.workspace.battleMemecoin as Program<BattleMemecoinClub>;
-  
+  const program = anchor.workspace.battleMemecoinClub as Program<BattleMemecoinClub>;
+
   let houseWallet: PublicKey;
   let houseBump: number;
-  let matchAccount: anchor.web3.Keypair;
   let bettorKeypair: anchor.web3.Keypair;
   let bettorKeypair2: anchor.web3.Keypair;
+  let bettorKeypair3: anchor.web3.Keypair;
   const matchId = "MATCH_001";
-  
+  const fighters = ["DOGE", "SHIB", "PEPE"];
+  const U64_MAX = new anchor.BN("18446744073709551615");
+
+  // Match PDAs and their SOL escrows
+  const matchPda = (id: string) => PublicKey.findProgramAddressSync(
+    [Buffer.from("match"), Buffer.from(id)],
+    program.programId
+  )[0];
+  const vaultPda = (match: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), match.toBuffer()],
+    program.programId
+  )[0];
+  const escrowPda = (match: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("escrow"), match.toBuffer()],
+    program.programId
+  )[0];
+  const betPda = (match: PublicKey, bettor: PublicKey) => PublicKey.findProgramAddressSync(
+    [Buffer.from("bet"), match.toBuffer(), bettor.toBuffer()],
+    program.programId
+  )[0];
+  // A wallet's position on a second fighter, when the match allows hedging
+  const hedgePda = (match: PublicKey, bettor: PublicKey, fighter: string) => PublicKey.findProgramAddressSync(
+    [Buffer.from("bet"), match.toBuffer(), bettor.toBuffer(), Buffer.from(fighter)],
+    program.programId
+  )[0];
+
+  // Every bet from the settled cursor onward, as (bet, bettor) pairs in index order
+  const pendingBets = async (match: PublicKey) => {
+    const matchState = await program.account.matchAccount.fetch(match);
+    const bets = await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: match.toBase58() } }
+    ]);
+    return bets
+      .filter(bet => bet.account.index >= matchState.settledCursor)
+      .sort((a, b) => a.account.index - b.account.index)
+      .flatMap(bet => [
+        { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: bet.account.bettor, isWritable: true, isSigner: false },
+      ]);
+  };
+
+  // Betting opened a minute ago; the close time and resolution deadline are seconds from now
+  const matchSchedule = (closesIn = 600, deadlineIn = 3600) => {
+    const now = Math.floor(Date.now() / 1000);
+    return {
+      bettingOpensAt: new anchor.BN(now - 60),
+      bettingClosesAt: new anchor.BN(now + closesIn),
+      expectedEndAt: new anchor.BN(now + Math.min(closesIn + 600, deadlineIn)),
+      resolutionDeadline: new anchor.BN(now + deadlineIn),
+    };
+  };
+
+  // The program checks the cluster clock, which can lag the local one
+  const waitForClock = async (timestamp: number) => {
+    for (;;) {
+      const blockTime = await provider.connection.getBlockTime(await provider.connection.getSlot());
+      if (blockTime !== null && blockTime >= timestamp) {
+        return;
+      }
+      await new Promise(resolve => setTimeout(resolve, 500));
+    }
+  };
+
+  const createMatch = async (id: string, schedule = matchSchedule()) => {
+    const match = matchPda(id);
+    await program.methods
+      .createMatchAccount(id, fighters, schedule)
+      .accountsPartial({
+        matchAccount: match,
+        escrow: escrowPda(match),
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    return match;
+  };
+
+  const placeBet = async (id: string, bettor: anchor.web3.Keypair, fighter: string, lamports: number, hedge = false) => {
+    const match = matchPda(id);
+    await program.methods
+      .placeBet(id, fighter, new anchor.BN(lamports))
+      .accountsPartial({
+        matchAccount: match,
+        bet: betPda(match, bettor.publicKey),
+        hedgeBet: hedge ? hedgePda(match, bettor.publicKey, fighter) : null,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        bettor: bettor.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bettor])
+      .rpc();
+  };
+
+  const fundedKeypair = async () => {
+    const keypair = anchor.web3.Keypair.generate();
+    const signature = await provider.connection.requestAirdrop(keypair.publicKey, 1 * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(signature);
+    return keypair;
+  };
+
+  // House terms for new matches: the defaults, with the given draw handling and hedging policy
+  const updateConfig = async (drawHandling: any, hedgingPolicy: any = { sameFighterOnly: {} }) => {
+    await program.methods
+      .updateConfig(500, U64_MAX, new anchor.BN(50_000_000), U64_MAX, drawHandling, 0, hedgingPolicy)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  };
+
+  const beginBattle = async (id: string) => {
+    await program.methods
+      .updateMatchStatus("Battle", id)
+      .accountsPartial({
+        matchAccount: matchPda(id),
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  };
+
+  const endMatch = async (id: string, result: any) => {
+    const match = matchPda(id);
+    await program.methods
+      .endMatch(id, result)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        treasury: provider.wallet.publicKey,
+        authority: provider.wallet.publicKey,
+        tournament: null,
+        nextMatch: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  const claimRefunds = async (id: string) => {
+    const match = matchPda(id);
+    await program.methods
+      .claimRefund(id, false)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(await pendingBets(match))
+      .rpc();
+  };
+
+  const claimWinnings = async (id: string, bettor: anchor.web3.Keypair) => {
+    const match = matchPda(id);
+    await program.methods
+      .claimWinnings(id)
+      .accountsPartial({
+        matchAccount: match,
+        bet: betPda(match, bettor.publicKey),
+        houseWallet: houseWallet,
+        bettor: bettor.publicKey,
+        escrow: escrowPda(match),
+      })
+      .signers([bettor])
+      .rpc();
+  };
+
+  const claimBetRefund = async (id: string, bettor: anchor.web3.Keypair) => {
+    const match = matchPda(id);
+    await program.methods
+      .claimBetRefund(id)
+      .accountsPartial({
+        matchAccount: match,
+        bet: betPda(match, bettor.publicKey),
+        houseWallet: houseWallet,
+        bettor: bettor.publicKey,
+        escrow: escrowPda(match),
+      })
+      .signers([bettor])
+      .rpc();
+  };
+
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  // The owner's associated token account for the mint, topped up with `amount`
+  const tokenAccount = async (mint: PublicKey, owner: PublicKey, tokenProgram: PublicKey, amount = 0) => {
+    const account = await getOrCreateAssociatedTokenAccount(
+      provider.connection, payer, mint, owner, false, undefined, undefined, tokenProgram
+    );
+    if (amount > 0) {
+      await mintTo(provider.connection, payer, mint, account.address, payer, amount, [], undefined, tokenProgram);
+    }
+    return account.address;
+  };
+
+  const tokenBalance = async (address: PublicKey, tokenProgram: PublicKey) =>
+    Number((await getAccount(provider.connection, address, undefined, tokenProgram)).amount);
+
+  // Token matches carry their own limits in mint units: 0.1 to 10 tokens of 6 decimals
+  const createTokenMatch = async (id: string, mint: PublicKey, tokenProgram: PublicKey) => {
+    const match = matchPda(id);
+    await program.methods
+      .createTokenMatchAccount(id, fighters, new anchor.BN(100_000), new anchor.BN(10_000_000), U64_MAX, matchSchedule())
+      .accountsPartial({
+        matchAccount: match,
+        betMint: mint,
+        vault: vaultPda(match),
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        tokenProgram: tokenProgram,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    return match;
+  };
+
+  const placeTokenBet = async (
+    id: string,
+    bettor: anchor.web3.Keypair,
+    fighter: string,
+    amount: number,
+    mint: PublicKey,
+    tokenProgram: PublicKey
+  ) => {
+    const match = matchPda(id);
+    await program.methods
+      .placeBet(id, fighter, new anchor.BN(amount))
+      .accountsPartial({
+        matchAccount: match,
+        bet: betPda(match, bettor.publicKey),
+        hedgeBet: null,
+        houseWallet: houseWallet,
+        escrow: null,
+        bettor: bettor.publicKey,
+        betMint: mint,
+        vault: vaultPda(match),
+        bettorTokenAccount: getAssociatedTokenAddressSync(mint, bettor.publicKey, false, tokenProgram),
+        tokenProgram: tokenProgram,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bettor])
+      .rpc();
+  };
+
+  const endTokenMatch = async (id: string, winner: string, mint: PublicKey, tokenProgram: PublicKey) => {
+    const match = matchPda(id);
+    await program.methods
+      .endMatch(id, { winner: { 0: winner } })
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: null,
+        treasury: provider.wallet.publicKey,
+        authority: provider.wallet.publicKey,
+        betMint: mint,
+        vault: vaultPda(match),
+        treasuryTokenAccount: getAssociatedTokenAddressSync(mint, provider.wallet.publicKey, false, tokenProgram),
+        tokenProgram: tokenProgram,
+        tournament: null,
+        nextMatch: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  // Token payouts go to each bettor's associated token account
+  const claimTokenPrizes = async (
+    id: string,
+    bettors: anchor.web3.Keypair[],
+    mint: PublicKey,
+    tokenProgram: PublicKey,
+    strict = false
+  ) => {
+    const match = matchPda(id);
+    await program.methods
+      .claimPrize(id, strict)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: null,
+        treasury: provider.wallet.publicKey,
+        authority: provider.wallet.publicKey,
+        betMint: mint,
+        vault: vaultPda(match),
+        tokenProgram: tokenProgram,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(bettors.flatMap(bettor => [
+        { pubkey: betPda(match, bettor.publicKey), isWritable: true, isSigner: false },
+        {
+          pubkey: getAssociatedTokenAddressSync(mint, bettor.publicKey, false, tokenProgram),
+          isWritable: true,
+          isSigner: false,
+        },
+      ]))
+      .rpc();
+  };
+
   before(async () => {
     // Find PDA for house wallet
     const [houseWalletPDA, bump] = await PublicKey.findProgramAddress(
@@ -25,242 +340,920 @@ describe("battle_memecoin", () => {
     );
     houseWallet = houseWalletPDA;
     houseBump = bump;
-    
-    // Create match account
-    matchAccount = anchor.web3.Keypair.generate();
-    
+
     // Create and fund bettor keypairs
     bettorKeypair = anchor.web3.Keypair.generate();
     bettorKeypair2 = anchor.web3.Keypair.generate();
-    
-    // Fund first bettor
-    const signature1 = await provider.connection.requestAirdrop(
-      bettorKeypair.publicKey,
-      1 * LAMPORTS_PER_SOL
-    );
-    await provider.connection.confirmTransaction(signature1);
-    
-    // Fund second bettor
-    const signature2 = await provider.connection.requestAirdrop(
-      bettorKeypair2.publicKey,
-      1 * LAMPORTS_PER_SOL
-    );
-    await provider.connection.confirmTransaction(signature2);
+    bettorKeypair3 = anchor.web3.Keypair.generate();
+
+    for (const bettor of [bettorKeypair, bettorKeypair2, bettorKeypair3]) {
+      const signature = await provider.connection.requestAirdrop(
+        bettor.publicKey,
+        1 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature);
+    }
+  });
+
+  it("Initialize house wallet", async () => {
+    const tx = await program.methods
+      .initialize(houseBump, provider.wallet.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    console.log("Initialize transaction signature", tx);
+
+    const account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.authority).to.eql(provider.wallet.publicKey);
+    expect(account.treasury).to.eql(provider.wallet.publicKey);
+    expect(account.paused).to.be.false;
+  });
+
+  it("Create match account with its escrow", async () => {
+    const match = await createMatch(matchId);
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.matchId).to.equal(matchId);
+    expect(account.fighters.map(pool => pool.name)).to.eql(fighters);
+    expect(account.status).to.deep.equal({ preparation: {} });
+    expect(account.nextBetIndex).to.equal(0);
+
+    const escrow = await program.account.escrow.fetch(escrowPda(match));
+    expect(escrow.matchAccount).to.eql(match);
+  });
+
+  it("Place bets across fighters", async () => {
+    const match = matchPda(matchId);
+    const escrowBefore = await provider.connection.getBalance(escrowPda(match));
+
+    await placeBet(matchId, bettorKeypair, "DOGE", 0.1 * LAMPORTS_PER_SOL);
+    await placeBet(matchId, bettorKeypair2, "DOGE", 0.2 * LAMPORTS_PER_SOL);
+    await placeBet(matchId, bettorKeypair3, "SHIB", 0.1 * LAMPORTS_PER_SOL);
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.fighters[0].totalBets.toNumber()).to.equal(0.3 * LAMPORTS_PER_SOL);
+    expect(account.fighters[0].betCount).to.equal(2);
+    expect(account.fighters[1].totalBets.toNumber()).to.equal(0.1 * LAMPORTS_PER_SOL);
+    expect(account.nextBetIndex).to.equal(3);
+
+    // Stakes sit in the match's escrow, not the house wallet
+    const escrowAfter = await provider.connection.getBalance(escrowPda(match));
+    expect(escrowAfter - escrowBefore).to.equal(0.4 * LAMPORTS_PER_SOL);
+
+    const bet = await program.account.bet.fetch(betPda(match, bettorKeypair2.publicKey));
+    expect(bet.index).to.equal(1);
+    expect(bet.amount.toNumber()).to.equal(0.2 * LAMPORTS_PER_SOL);
+  });
+
+  it("Cancelled bets stay behind as markers", async () => {
+    const cancelledMatchId = "MATCH_CANCEL";
+    const match = await createMatch(cancelledMatchId);
+    await placeBet(cancelledMatchId, bettorKeypair, "PEPE", 0.1 * LAMPORTS_PER_SOL);
+    const bet = betPda(match, bettorKeypair.publicKey);
 
-    // Initialize match account
     await program.methods
-      .createMatchAccount()
-      .accounts({
-        matchAccount: matchAccount.publicKey,
+      .cancelBet(cancelledMatchId)
+      .accountsPartial({
+        matchAccount: match,
+        bet: bet,
+        mainBet: null,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        bettor: bettorKeypair.publicKey,
+        treasury: provider.wallet.publicKey,
+      })
+      .signers([bettorKeypair])
+      .rpc();
+
+    const marker = await program.account.bet.fetch(bet);
+    expect(marker.amount.toNumber()).to.equal(0);
+    expect(marker.index).to.equal(0);
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.fighters[2].betCount).to.equal(0);
+
+    // Betting again reuses the marker's place in line
+    await placeBet(cancelledMatchId, bettorKeypair, "PEPE", 0.1 * LAMPORTS_PER_SOL);
+    const rebet = await program.account.bet.fetch(bet);
+    expect(rebet.index).to.equal(0);
+    expect((await program.account.matchAccount.fetch(match)).nextBetIndex).to.equal(1);
+  });
+
+  it("End match and take the fee from the losing pools", async () => {
+    const match = matchPda(matchId);
+
+    // First update match status to Battle
+    const updateTx = await program.methods
+      .updateMatchStatus("Battle", matchId)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
         authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("Update match status transaction signature", updateTx);
+
+    const escrowBefore = await provider.connection.getBalance(escrowPda(match));
+
+    // Then end the match
+    const tx = await program.methods
+      .endMatch(matchId, { winner: { 0: "DOGE" } })
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        treasury: provider.wallet.publicKey,
+        authority: provider.wallet.publicKey,
+        tournament: null,
+        nextMatch: null,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .signers([matchAccount])
       .rpc();
+
+    console.log("End match transaction signature", tx);
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.winner).to.equal("DOGE");
+    expect(account.status).to.deep.equal({ completed: {} });
+
+    // 5% of the 0.1 SOL losing pool goes to the treasury, the rest to DOGE backers
+    const escrowAfter = await provider.connection.getBalance(escrowPda(match));
+    expect(escrowBefore - escrowAfter).to.equal(5_000_000);
+    expect(account.prizePool.toNumber()).to.equal(95_000_000);
+    expect(account.placements).to.have.length(1);
+    expect(account.placements[0].fighter).to.equal("DOGE");
+    expect(account.placements[0].prizePool.toNumber()).to.equal(95_000_000);
   });
 
-  it("Initialize house wallet", async () => {
+  it("Claim prize pays shares and the rounding remainder", async () => {
+    const match = matchPda(matchId);
+    const balanceBefore = await provider.connection.getBalance(bettorKeypair.publicKey);
+    const balanceBefore2 = await provider.connection.getBalance(bettorKeypair2.publicKey);
+    const balanceBefore3 = await provider.connection.getBalance(bettorKeypair3.publicKey);
+
+    const tx = await program.methods
+      .claimPrize(matchId, false)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        treasury: provider.wallet.publicKey,
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(await pendingBets(match))
+      .rpc();
+
+    console.log("Claim prize transaction signature", tx);
+
+    // 95,000,000 split 1:2 floors to 31,666,666 and 63,333,333; the last
+    // winner paid picks up the leftover lamport
+    const balanceAfter = await provider.connection.getBalance(bettorKeypair.publicKey);
+    const balanceAfter2 = await provider.connection.getBalance(bettorKeypair2.publicKey);
+    const balanceAfter3 = await provider.connection.getBalance(bettorKeypair3.publicKey);
+    expect(balanceAfter - balanceBefore).to.equal(100_000_000 + 31_666_666);
+    expect(balanceAfter2 - balanceBefore2).to.equal(200_000_000 + 63_333_333 + 1);
+    expect(balanceAfter3).to.equal(balanceBefore3);
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.claimedCount).to.equal(2);
+    expect(account.paidOut.toNumber()).to.equal(395_000_000);
+    expect(account.settledCursor).to.equal(3);
+
+    const bet = await program.account.bet.fetch(betPda(match, bettorKeypair.publicKey));
+    expect(bet.claimed).to.be.true;
+  });
+
+  it("Cannot claim prize twice", async () => {
+    const match = matchPda(matchId);
+    const balanceBefore = await provider.connection.getBalance(bettorKeypair.publicKey);
+
+    // Passing already-claimed bets again pays nothing
+    const bets = await program.account.bet.all([
+      { memcmp: { offset: 8, bytes: match.toBase58() } }
+    ]);
+    const allBets = bets
+      .sort((a, b) => a.account.index - b.account.index)
+      .flatMap(bet => [
+        { pubkey: bet.publicKey, isWritable: true, isSigner: false },
+        { pubkey: bet.account.bettor, isWritable: true, isSigner: false },
+      ]);
+
     try {
-      const tx = await program.methods
-        .initialize(houseBump)
-        .accounts({
+      await program.methods
+        .claimPrize(matchId, false)
+        .accountsPartial({
+          matchAccount: match,
           houseWallet: houseWallet,
+          escrow: escrowPda(match),
+          treasury: provider.wallet.publicKey,
           authority: provider.wallet.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
+        .remainingAccounts(allBets)
         .rpc();
-      
-      console.log("Initialize transaction signature", tx);
-      
-      const account = await program.account.houseWallet.fetch(houseWallet);
-      expect(account.authority).to.eql(provider.wallet.publicKey);
-      expect(account.paused).to.be.false;
+      expect.fail("Bets behind the settled cursor should be rejected");
     } catch (error) {
-      console.error("Error:", error);
-      throw error;
+      expect(error.toString()).to.include("BetOutOfOrder");
     }
+
+    const balanceAfter = await provider.connection.getBalance(bettorKeypair.publicKey);
+    expect(balanceAfter).to.equal(balanceBefore);
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.paidOut.toNumber()).to.equal(395_000_000);
   });
 
-  it("Place bets on both fighters", async () => {
+  it("Cannot cancel a bet after it was refunded", async () => {
+    const refundedMatchId = "MATCH_REFUND";
+    const match = await createMatch(refundedMatchId);
+    await placeBet(refundedMatchId, bettorKeypair, "DOGE", 0.1 * LAMPORTS_PER_SOL);
+    const bet = betPda(match, bettorKeypair.publicKey);
+    const balanceBefore = await provider.connection.getBalance(bettorKeypair.publicKey);
+
+    await program.methods
+      .emergencyRefund(refundedMatchId, false)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(await pendingBets(match))
+      .rpc();
+
+    const balanceRefunded = await provider.connection.getBalance(bettorKeypair.publicKey);
+    expect(balanceRefunded - balanceBefore).to.equal(0.1 * LAMPORTS_PER_SOL);
+    expect((await program.account.bet.fetch(bet)).claimed).to.be.true;
+
     try {
-      const fighter1 = "DOGE";
-      const fighter2 = "SHIB";
-      const amount = new anchor.BN(0.1 * LAMPORTS_PER_SOL); // 0.1 SOL
-
-      // Place bet on fighter1
-      const tx1 = await program.methods
-        .placeBet(matchId, fighter1, amount)
-        .accounts({
-          matchAccount: matchAccount.publicKey,
+      await program.methods
+        .cancelBet(refundedMatchId)
+        .accountsPartial({
+          matchAccount: match,
+          bet: bet,
+          mainBet: null,
           houseWallet: houseWallet,
+          escrow: escrowPda(match),
           bettor: bettorKeypair.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
+          treasury: provider.wallet.publicKey,
         })
         .signers([bettorKeypair])
         .rpc();
+      expect.fail("A refunded bet should not be withdrawable");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyClaimed");
+    }
 
-      console.log("Place bet 1 transaction signature", tx1);
+    // The refund was paid exactly once
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.status).to.deep.equal({ refund: {} });
+    expect(account.paidOut.toNumber()).to.equal(0.1 * LAMPORTS_PER_SOL);
+  });
 
-      // Place bet on fighter2
-      const tx2 = await program.methods
-        .placeBet(matchId, fighter2, amount)
-        .accounts({
-          matchAccount: matchAccount.publicKey,
-          houseWallet: houseWallet,
-          bettor: bettorKeypair2.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
-        })
-        .signers([bettorKeypair2])
-        .rpc();
+  it("A draw refunds every stake in full", async () => {
+    const drawMatchId = "MATCH_DRAW";
+    const match = await createMatch(drawMatchId);
+    const bettor = await fundedKeypair();
+    const bettor2 = await fundedKeypair();
+    await placeBet(drawMatchId, bettor, "DOGE", 100_000_000);
+    await placeBet(drawMatchId, bettor2, "SHIB", 200_000_000);
+    await beginBattle(drawMatchId);
+
+    const escrowBefore = await provider.connection.getBalance(escrowPda(match));
+    await endMatch(drawMatchId, { draw: {} });
+
+    // FullRefund is the default, so no fee leaves the escrow
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.status).to.deep.equal({ draw: {} });
+    expect(account.winner).to.be.null;
+    expect(account.prizePool.toNumber()).to.equal(300_000_000);
+    expect(await provider.connection.getBalance(escrowPda(match))).to.equal(escrowBefore);
+
+    const balanceBefore = await provider.connection.getBalance(bettor.publicKey);
+    const balanceBefore2 = await provider.connection.getBalance(bettor2.publicKey);
+    await claimRefunds(drawMatchId);
+
+    expect(await provider.connection.getBalance(bettor.publicKey) - balanceBefore).to.equal(100_000_000);
+    expect(await provider.connection.getBalance(bettor2.publicKey) - balanceBefore2).to.equal(200_000_000);
+    expect((await program.account.matchAccount.fetch(match)).paidOut.toNumber()).to.equal(300_000_000);
+  });
+
+  it("A no contest under RefundMinusFee refunds stakes less the fee", async () => {
+    const noContestMatchId = "MATCH_NO_CONTEST";
+    await updateConfig({ refundMinusFee: {} });
+    const match = await createMatch(noContestMatchId);
+    // Matches keep the draw handling they were created with
+    await updateConfig({ fullRefund: {} });
+
+    const bettor = await fundedKeypair();
+    const bettor2 = await fundedKeypair();
+    await placeBet(noContestMatchId, bettor, "DOGE", 100_000_000);
+    await placeBet(noContestMatchId, bettor2, "SHIB", 200_000_000);
+    await beginBattle(noContestMatchId);
+
+    const escrowBefore = await provider.connection.getBalance(escrowPda(match));
+    await endMatch(noContestMatchId, { noContest: {} });
+
+    // 5% of the whole 0.3 SOL pool goes to the treasury
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.status).to.deep.equal({ noContest: {} });
+    expect(account.drawHandling).to.deep.equal({ refundMinusFee: {} });
+    expect(account.prizePool.toNumber()).to.equal(285_000_000);
+    expect(escrowBefore - await provider.connection.getBalance(escrowPda(match))).to.equal(15_000_000);
+
+    const balanceBefore = await provider.connection.getBalance(bettor.publicKey);
+    const balanceBefore2 = await provider.connection.getBalance(bettor2.publicKey);
+    await claimRefunds(noContestMatchId);
+
+    // Each stake comes back pro rata from what is left
+    expect(await provider.connection.getBalance(bettor.publicKey) - balanceBefore).to.equal(95_000_000);
+    expect(await provider.connection.getBalance(bettor2.publicKey) - balanceBefore2).to.equal(190_000_000);
+    expect((await program.account.matchAccount.fetch(match)).paidOut.toNumber()).to.equal(285_000_000);
+  });
+
+  it("Winners claim their own prize", async () => {
+    const winningsMatchId = "MATCH_WINNINGS";
+    const match = await createMatch(winningsMatchId);
+    const winner = await fundedKeypair();
+    const loser = await fundedKeypair();
+    await placeBet(winningsMatchId, winner, "DOGE", 100_000_000);
+    await placeBet(winningsMatchId, loser, "SHIB", 100_000_000);
+    await beginBattle(winningsMatchId);
+    await endMatch(winningsMatchId, { winner: { 0: "DOGE" } });
+
+    // The stake plus the losing pool less the 5% fee, without the operator
+    const balanceBefore = await provider.connection.getBalance(winner.publicKey);
+    await claimWinnings(winningsMatchId, winner);
+    expect(await provider.connection.getBalance(winner.publicKey) - balanceBefore).to.equal(195_000_000);
+    expect((await program.account.bet.fetch(betPda(match, winner.publicKey))).claimed).to.be.true;
+
+    try {
+      await claimWinnings(winningsMatchId, winner);
+      expect.fail("A prize should only be paid once");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyClaimed");
+    }
+
+    try {
+      await claimWinnings(winningsMatchId, loser);
+      expect.fail("A losing bet has no prize to claim");
+    } catch (error) {
+      expect(error.toString()).to.include("NotAWinner");
+    }
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.claimedCount).to.equal(1);
+    expect(account.paidOut.toNumber()).to.equal(195_000_000);
+  });
+
+  it("Bettors claim their own refund", async () => {
+    const selfRefundMatchId = "MATCH_SELF_REFUND";
+    const match = await createMatch(selfRefundMatchId);
+    const bettor = await fundedKeypair();
+    await placeBet(selfRefundMatchId, bettor, "DOGE", 100_000_000);
+
+    try {
+      await claimBetRefund(selfRefundMatchId, bettor);
+      expect.fail("Stakes of a live match are not refundable");
+    } catch (error) {
+      expect(error.toString()).to.include("NotRefundable");
+    }
+
+    // An emergency refund page with no bets just moves the match to Refund
+    await program.methods
+      .emergencyRefund(selfRefundMatchId, false)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        escrow: escrowPda(match),
+        authority: provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    expect((await program.account.matchAccount.fetch(match)).status).to.deep.equal({ refund: {} });
 
-      console.log("Place bet 2 transaction signature", tx2);
+    const balanceBefore = await provider.connection.getBalance(bettor.publicKey);
+    await claimBetRefund(selfRefundMatchId, bettor);
+    expect(await provider.connection.getBalance(bettor.publicKey) - balanceBefore).to.equal(100_000_000);
 
-      const account = await program.account.matchAccount.fetch(matchAccount.publicKey);
-      expect(account.matchId).to.equal(matchId);
-      expect(account.fighter1).to.equal(fighter1);
-      expect(account.fighter2).to.equal(fighter2);
-      expect(account.totalBetsFighter1.toString()).to.equal(amount.toString());
-      expect(account.totalBetsFighter2.toString()).to.equal(amount.toString());
+    try {
+      await claimBetRefund(selfRefundMatchId, bettor);
+      expect.fail("A refund should only be paid once");
     } catch (error) {
-      console.error("Error:", error);
-      throw error;
+      expect(error.toString()).to.include("AlreadyClaimed");
     }
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.claimedCount).to.equal(1);
+    expect(account.paidOut.toNumber()).to.equal(100_000_000);
   });
 
-  it("End match and distribute prizes", async () => {
+  it("Authority moves only once the proposed key accepts", async () => {
+    const newAuthority = await fundedKeypair();
+    const stranger = await fundedKeypair();
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // The current authority stays in control until the transfer is accepted
+    let account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.authority).to.eql(provider.wallet.publicKey);
+    expect(account.pendingAuthority).to.eql(newAuthority.publicKey);
+
     try {
-      const winner = "DOGE";
-      const treasuryBalanceBefore = await provider.connection.getBalance(provider.wallet.publicKey);
-      
-      // First update match status to Battle
-      const updateTx = await program.methods
-        .updateMatchStatus("Battle")
-        .accounts({
-          matchAccount: matchAccount.publicKey,
+      await program.methods
+        .acceptAuthority()
+        .accountsPartial({
           houseWallet: houseWallet,
-          authority: provider.wallet.publicKey,
+          newAuthority: stranger.publicKey,
         })
+        .signers([stranger])
         .rpc();
-      
-      console.log("Update match status transaction signature", updateTx);
-      
-      // Then end the match
-      const tx = await program.methods
-        .endMatch(matchId, winner)
-        .accounts({
-          matchAccount: matchAccount.publicKey,
+      expect.fail("Only the proposed key can accept");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    await program.methods
+      .acceptAuthority()
+      .accountsPartial({
+        houseWallet: houseWallet,
+        newAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.authority).to.eql(newAuthority.publicKey);
+    expect(account.pendingAuthority).to.be.null;
+
+    try {
+      await program.methods
+        .setTreasury(provider.wallet.publicKey)
+        .accountsPartial({
           houseWallet: houseWallet,
-          treasury: provider.wallet.publicKey,
           authority: provider.wallet.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
-
-      console.log("End match transaction signature", tx);
-
-      const account = await program.account.matchAccount.fetch(matchAccount.publicKey);
-      expect(account.winner).to.not.be.null;
-      expect(account.winner.toString()).to.equal(winner);
-      expect(account.status).to.deep.equal({ completed: {} });
-
-      // Verify fee was transferred to treasury
-      const treasuryBalanceAfter = await provider.connection.getBalance(provider.wallet.publicKey);
-      const actualFee = treasuryBalanceAfter - treasuryBalanceBefore;
-      
-      // The actual fee from program is 4990016 lamports (verified from multiple runs)
-      const expectedFee = 4990016;
-      expect(actualFee).to.equal(expectedFee);
-      
-      console.log(`Treasury fee received: ${actualFee} lamports`);
+      expect.fail("The previous authority should have lost its rights");
     } catch (error) {
-      console.error("Error:", error);
-      throw error;
+      expect(error.toString()).to.include("Unauthorized");
     }
+
+    // Hand authority back for the rest of the suite
+    await program.methods
+      .proposeAuthority(provider.wallet.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accountsPartial({
+        houseWallet: houseWallet,
+        newAuthority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.authority).to.eql(provider.wallet.publicKey);
   });
 
-  it("Claim prize", async () => {
+  it("Roles grant only their own permissions", async () => {
+    const operator = await fundedKeypair();
+    const pauser = await fundedKeypair();
+    const roleMatchId = "MATCH_OPERATOR";
+    const match = matchPda(roleMatchId);
+    const createAsOperator = () => program.methods
+      .createMatchAccount(roleMatchId, fighters, matchSchedule())
+      .accountsPartial({
+        matchAccount: match,
+        escrow: escrowPda(match),
+        houseWallet: houseWallet,
+        authority: operator.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([operator])
+      .rpc();
+    const pauseAs = (signer: anchor.web3.Keypair, paused: boolean) => program.methods
+      .setPauseState(paused)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: signer.publicKey,
+      })
+      .signers([signer])
+      .rpc();
+
+    await program.methods
+      .addOperator(operator.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .addPauser(pauser.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    let account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.operators).to.deep.include(operator.publicKey);
+    expect(account.pausers).to.deep.include(pauser.publicKey);
+
     try {
-      const balanceBefore = await provider.connection.getBalance(bettorKeypair.publicKey);
-
-      // Get all winning bettors
-      const matchState = await program.account.matchAccount.fetch(matchAccount.publicKey);
-      const winningBettors = matchState.bets
-        .filter(bet => !bet.claimed && bet.fighter === matchState.winner)
-        .map(bet => ({ pubkey: bet.bettor, isWritable: true, isSigner: false }));
-
-      const tx = await program.methods
-        .claimPrize(matchId)
-        .accounts({
-          matchAccount: matchAccount.publicKey,
+      await program.methods
+        .addOperator(operator.publicKey)
+        .accountsPartial({
           houseWallet: houseWallet,
-          treasury: provider.wallet.publicKey,
           authority: provider.wallet.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
         })
-        .remainingAccounts(winningBettors)
         .rpc();
+      expect.fail("A member cannot be added twice");
+    } catch (error) {
+      expect(error.toString()).to.include("RoleMemberExists");
+    }
 
-      console.log("Claim prize transaction signature", tx);
-
-      const balanceAfter = await provider.connection.getBalance(bettorKeypair.publicKey);
-      expect(balanceAfter).to.be.greaterThan(balanceBefore);
+    // Operators run matches, but cannot pause or hand out roles
+    await createAsOperator();
+    expect((await program.account.matchAccount.fetch(match)).matchId).to.equal(roleMatchId);
 
-      const updatedMatch = await program.account.matchAccount.fetch(matchAccount.publicKey);
-      expect(updatedMatch.bets[0].claimed).to.be.true;
+    try {
+      await pauseAs(operator, true);
+      expect.fail("Operators cannot pause the program");
     } catch (error) {
-      console.error("Error:", error);
-      throw error;
+      expect(error.toString()).to.include("Unauthorized");
     }
-  });
 
-  it("Cannot claim prize twice", async () => {
     try {
-      // Get all winning bettors (should be empty now)
-      const matchState = await program.account.matchAccount.fetch(matchAccount.publicKey);
-      const winningBettors = matchState.bets
-        .filter(bet => !bet.claimed && bet.fighter === matchState.winner)
-        .map(bet => ({ pubkey: bet.bettor, isWritable: true, isSigner: false }));
-
-      const tx = await program.methods
-        .claimPrize(matchId)
-        .accounts({
-          matchAccount: matchAccount.publicKey,
+      await program.methods
+        .addResolver(operator.publicKey)
+        .accountsPartial({
           houseWallet: houseWallet,
-          treasury: provider.wallet.publicKey,
-          authority: provider.wallet.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
+          authority: operator.publicKey,
         })
-        .remainingAccounts(winningBettors)
+        .signers([operator])
         .rpc();
+      expect.fail("Only the authority manages roles");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
 
-      console.log("Second claim attempt transaction signature", tx);
-      
-      const updatedMatch = await program.account.matchAccount.fetch(matchAccount.publicKey);
-      const unclaimedBets = updatedMatch.bets.filter(bet => !bet.claimed && bet.fighter === updatedMatch.winner);
-      expect(unclaimedBets.length).to.equal(0);
+    // Pausers can pause, and nothing else
+    await pauseAs(pauser, true);
+    expect((await program.account.houseWallet.fetch(houseWallet)).paused).to.be.true;
+    await pauseAs(pauser, false);
+    expect((await program.account.houseWallet.fetch(houseWallet)).paused).to.be.false;
+
+    await program.methods
+      .removeOperator(operator.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    await program.methods
+      .removePauser(pauser.publicKey)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.operators).to.not.deep.include(operator.publicKey);
+
+    try {
+      await pauseAs(pauser, true);
+      expect.fail("A removed pauser has no rights left");
     } catch (error) {
-      console.error("Error:", error);
-      throw error;
+      expect(error.toString()).to.include("Unauthorized");
     }
   });
 
-  it("Set program to paused state", async () => {
+  it("Token matches take bets, fees and prizes in the bet mint", async () => {
+    const tokenMatchId = "MATCH_TOKEN";
+    const mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+    const match = await createTokenMatch(tokenMatchId, mint, TOKEN_PROGRAM_ID);
+    const winner = await fundedKeypair();
+    const loser = await fundedKeypair();
+    const winnerTokens = await tokenAccount(mint, winner.publicKey, TOKEN_PROGRAM_ID, 5_000_000);
+    await tokenAccount(mint, loser.publicKey, TOKEN_PROGRAM_ID, 5_000_000);
+    const treasuryTokens = await tokenAccount(mint, provider.wallet.publicKey, TOKEN_PROGRAM_ID);
+
+    let account = await program.account.matchAccount.fetch(match);
+    expect(account.betMint).to.eql(mint);
+    expect(account.minBet.toNumber()).to.equal(100_000);
+
     try {
-      const tx = await program.methods
-        .setPauseState(true)
-        .accounts({
-          houseWallet: houseWallet,
-          authority: provider.wallet.publicKey,
-        })
-        .rpc();
+      await placeTokenBet(tokenMatchId, winner, "DOGE", 50_000, mint, TOKEN_PROGRAM_ID);
+      expect.fail("Bets below the match's minimum should be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("BetTooSmall");
+    }
+
+    await placeTokenBet(tokenMatchId, winner, "DOGE", 1_000_000, mint, TOKEN_PROGRAM_ID);
+    await placeTokenBet(tokenMatchId, loser, "SHIB", 1_000_000, mint, TOKEN_PROGRAM_ID);
+    expect(await tokenBalance(vaultPda(match), TOKEN_PROGRAM_ID)).to.equal(2_000_000);
 
-      console.log("Set pause state transaction signature", tx);
+    await beginBattle(tokenMatchId);
+    await endTokenMatch(tokenMatchId, "DOGE", mint, TOKEN_PROGRAM_ID);
 
-      const account = await program.account.houseWallet.fetch(houseWallet);
-      expect(account.paused).to.be.true;
+    // 5% of the losing pool is paid to the treasury's token account
+    account = await program.account.matchAccount.fetch(match);
+    expect(account.prizePool.toNumber()).to.equal(950_000);
+    expect(await tokenBalance(treasuryTokens, TOKEN_PROGRAM_ID)).to.equal(50_000);
+
+    // The loser's bet is stepped over; the winner gets the stake plus the prize pool
+    await claimTokenPrizes(tokenMatchId, [winner, loser], mint, TOKEN_PROGRAM_ID);
+    expect(await tokenBalance(winnerTokens, TOKEN_PROGRAM_ID)).to.equal(4_000_000 + 1_950_000);
+    expect(await tokenBalance(vaultPda(match), TOKEN_PROGRAM_ID)).to.equal(0);
+
+    account = await program.account.matchAccount.fetch(match);
+    expect(account.paidOut.toNumber()).to.equal(1_950_000);
+    expect(account.settledCursor).to.equal(2);
+  });
+
+  it("Betting locks itself once the close time passes", async () => {
+    const scheduledMatchId = "MATCH_SCHEDULED";
+    const schedule = matchSchedule(10);
+    const match = await createMatch(scheduledMatchId, schedule);
+    const bettor = await fundedKeypair();
+    await placeBet(scheduledMatchId, bettor, "DOGE", 100_000_000);
+
+    // Any wallet may crank the match, here the bettor
+    const startBattle = () => program.methods
+      .startBattle(scheduledMatchId)
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        cranker: bettor.publicKey,
+      })
+      .signers([bettor])
+      .rpc();
+
+    try {
+      await startBattle();
+      expect.fail("Betting should stay open until the close time");
     } catch (error) {
-      console.error("Error:", error);
-      throw error;
+      expect(error.toString()).to.include("BettingStillOpen");
     }
+
+    await waitForClock(schedule.bettingClosesAt.toNumber());
+
+    try {
+      await placeBet(scheduledMatchId, bettor, "DOGE", 100_000_000);
+      expect.fail("Late bets should be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("BettingClosed");
+    }
+
+    await startBattle();
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.status).to.deep.equal({ battle: {} });
+    expect(account.fighters[0].totalBets.toNumber()).to.equal(100_000_000);
+  });
+
+  it("Anyone can expire an abandoned match, and its bettors refund themselves while paused", async () => {
+    const abandonedMatchId = "MATCH_ABANDONED";
+    const schedule = matchSchedule(5, 10);
+    const match = await createMatch(abandonedMatchId, schedule);
+    const bettor = await fundedKeypair();
+    await placeBet(abandonedMatchId, bettor, "DOGE", 100_000_000);
+
+    const expireMatch = () => program.methods
+      .expireMatch(abandonedMatchId)
+      .accountsPartial({
+        matchAccount: match,
+        cranker: bettor.publicKey,
+      })
+      .signers([bettor])
+      .rpc();
+    const setPaused = (paused: boolean) => program.methods
+      .setPauseState(paused)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await expireMatch();
+      expect.fail("The resolver still has time to end the match");
+    } catch (error) {
+      expect(error.toString()).to.include("ResolutionDeadlineNotReached");
+    }
+
+    await waitForClock(schedule.resolutionDeadline.toNumber());
+
+    // Neither step needs the operator, nor an unpaused program
+    await setPaused(true);
+    try {
+      await expireMatch();
+      expect((await program.account.matchAccount.fetch(match)).status).to.deep.equal({ refund: {} });
+
+      const balanceBefore = await provider.connection.getBalance(bettor.publicKey);
+      await claimBetRefund(abandonedMatchId, bettor);
+      expect(await provider.connection.getBalance(bettor.publicKey) - balanceBefore).to.equal(100_000_000);
+    } finally {
+      await setPaused(false);
+    }
+  });
+
+  it("Repeat bets top up the position, and hedges need the match to allow them", async () => {
+    const topUpMatchId = "MATCH_TOP_UP";
+    const match = await createMatch(topUpMatchId);
+    const bettor = await fundedKeypair();
+    await placeBet(topUpMatchId, bettor, "DOGE", 100_000_000);
+    await placeBet(topUpMatchId, bettor, "DOGE", 50_000_000);
+
+    const bet = await program.account.bet.fetch(betPda(match, bettor.publicKey));
+    expect(bet.amount.toNumber()).to.equal(150_000_000);
+    let account = await program.account.matchAccount.fetch(match);
+    expect(account.fighters[0].totalBets.toNumber()).to.equal(150_000_000);
+    expect(account.fighters[0].betCount).to.equal(1);
+
+    // SameFighterOnly is the default policy
+    try {
+      await placeBet(topUpMatchId, bettor, "SHIB", 100_000_000, true);
+      expect.fail("Hedging should be rejected under SameFighterOnly");
+    } catch (error) {
+      expect(error.toString()).to.include("HedgingNotAllowed");
+    }
+
+    const hedgeMatchId = "MATCH_HEDGE";
+    await updateConfig({ fullRefund: {} }, { allowHedging: {} });
+    const hedgeMatch = await createMatch(hedgeMatchId);
+    await updateConfig({ fullRefund: {} });
+
+    // A bet on another fighter opens a separate hedge bet alongside the main one
+    await placeBet(hedgeMatchId, bettor, "DOGE", 100_000_000);
+    await placeBet(hedgeMatchId, bettor, "SHIB", 60_000_000, true);
+
+    const mainBet = await program.account.bet.fetch(betPda(hedgeMatch, bettor.publicKey));
+    expect(mainBet.fighter).to.equal("DOGE");
+    expect(mainBet.amount.toNumber()).to.equal(100_000_000);
+    expect(mainBet.hedgeCount).to.equal(1);
+    expect(mainBet.walletTotal.toNumber()).to.equal(160_000_000);
+    const hedgeBet = await program.account.bet.fetch(hedgePda(hedgeMatch, bettor.publicKey, "SHIB"));
+    expect(hedgeBet.fighter).to.equal("SHIB");
+    expect(hedgeBet.amount.toNumber()).to.equal(60_000_000);
+    expect(hedgeBet.hedge).to.be.true;
+
+    account = await program.account.matchAccount.fetch(hedgeMatch);
+    expect(account.fighters[0].totalBets.toNumber()).to.equal(100_000_000);
+    expect(account.fighters[1].totalBets.toNumber()).to.equal(60_000_000);
+  });
+
+  it("Per-match caps limit single bets, wallets and fighter pools", async () => {
+    const cappedMatchId = "MATCH_CAPPED";
+    const match = await createMatch(cappedMatchId);
+    const setBetLimits = (minBet: number, maxBet: number, maxWalletTotal: number, maxFighterPool: number) => program.methods
+      .setBetLimits(
+        cappedMatchId,
+        new anchor.BN(minBet),
+        new anchor.BN(maxBet),
+        new anchor.BN(maxWalletTotal),
+        new anchor.BN(maxFighterPool)
+      )
+      .accountsPartial({
+        matchAccount: match,
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await setBetLimits(0, 200_000_000, 300_000_000, 400_000_000);
+      expect.fail("A zero minimum bet should be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidBetLimits");
+    }
+
+    await setBetLimits(50_000_000, 200_000_000, 300_000_000, 400_000_000);
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.maxBet.toNumber()).to.equal(200_000_000);
+    expect(account.maxWalletTotal.toNumber()).to.equal(300_000_000);
+    expect(account.maxFighterPool.toNumber()).to.equal(400_000_000);
+
+    const whale = await fundedKeypair();
+    const bettor = await fundedKeypair();
+    const latecomer = await fundedKeypair();
+
+    try {
+      await placeBet(cappedMatchId, whale, "DOGE", 250_000_000);
+      expect.fail("Bets over the maximum should be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("BetTooLarge");
+    }
+
+    // Each bet is within the maximum, but the wallet's total is not
+    await placeBet(cappedMatchId, whale, "DOGE", 200_000_000);
+    try {
+      await placeBet(cappedMatchId, whale, "DOGE", 150_000_000);
+      expect.fail("The wallet cap should count earlier bets");
+    } catch (error) {
+      expect(error.toString()).to.include("WalletLimitExceeded");
+    }
+
+    // DOGE's pool fills up exactly, then takes nothing more
+    await placeBet(cappedMatchId, bettor, "DOGE", 200_000_000);
+    try {
+      await placeBet(cappedMatchId, latecomer, "DOGE", 50_000_000);
+      expect.fail("The fighter pool cap should be enforced");
+    } catch (error) {
+      expect(error.toString()).to.include("FighterPoolLimitExceeded");
+    }
+    await placeBet(cappedMatchId, latecomer, "SHIB", 50_000_000);
+
+    const capped = await program.account.matchAccount.fetch(match);
+    expect(capped.fighters[0].totalBets.toNumber()).to.equal(400_000_000);
+    expect(capped.fighters[1].totalBets.toNumber()).to.equal(50_000_000);
+  });
+
+  it("Strict payouts fail the whole page with the unpaid bettor's key", async () => {
+    const strictMatchId = "MATCH_STRICT";
+
+    // A Token-2022 mint whose permanent delegate can pull tokens out of the match vault
+    const mint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.PermanentDelegate]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: payer.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializePermanentDelegateInstruction(mint.publicKey, payer.publicKey, TOKEN_2022_PROGRAM_ID),
+        createInitializeMintInstruction(mint.publicKey, 6, payer.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [mint]
+    );
+
+    const match = await createTokenMatch(strictMatchId, mint.publicKey, TOKEN_2022_PROGRAM_ID);
+    const winner = await fundedKeypair();
+    const loser = await fundedKeypair();
+    const winnerTokens = await tokenAccount(mint.publicKey, winner.publicKey, TOKEN_2022_PROGRAM_ID, 5_000_000);
+    await tokenAccount(mint.publicKey, loser.publicKey, TOKEN_2022_PROGRAM_ID, 5_000_000);
+    await tokenAccount(mint.publicKey, provider.wallet.publicKey, TOKEN_2022_PROGRAM_ID);
+
+    await placeTokenBet(strictMatchId, winner, "DOGE", 1_000_000, mint.publicKey, TOKEN_2022_PROGRAM_ID);
+    await placeTokenBet(strictMatchId, loser, "SHIB", 1_000_000, mint.publicKey, TOKEN_2022_PROGRAM_ID);
+    await beginBattle(strictMatchId);
+    await endTokenMatch(strictMatchId, "DOGE", mint.publicKey, TOKEN_2022_PROGRAM_ID);
+
+    // Leave the vault short of the winner's 1,950,000
+    await burn(
+      provider.connection, payer, vaultPda(match), mint.publicKey, payer, 1_000_000, [], undefined, TOKEN_2022_PROGRAM_ID
+    );
+    expect(await tokenBalance(vaultPda(match), TOKEN_2022_PROGRAM_ID)).to.equal(950_000);
+
+    try {
+      await claimTokenPrizes(strictMatchId, [winner, loser], mint.publicKey, TOKEN_2022_PROGRAM_ID, true);
+      expect.fail("A strict page should fail when a bettor cannot be paid");
+    } catch (error) {
+      expect(error.toString()).to.include("PayoutShortfall");
+      expect(error.toString()).to.include(winner.publicKey.toBase58());
+    }
+
+    // Without strict mode the page succeeds but skips the winner and holds the cursor
+    await claimTokenPrizes(strictMatchId, [winner, loser], mint.publicKey, TOKEN_2022_PROGRAM_ID);
+    expect(await tokenBalance(winnerTokens, TOKEN_2022_PROGRAM_ID)).to.equal(4_000_000);
+    expect((await program.account.bet.fetch(betPda(match, winner.publicKey))).claimed).to.be.false;
+
+    const account = await program.account.matchAccount.fetch(match);
+    expect(account.paidOut.toNumber()).to.equal(0);
+    expect(account.settledCursor).to.equal(0);
+  });
+
+  it("Set program to paused state", async () => {
+    const tx = await program.methods
+      .setPauseState(true)
+      .accountsPartial({
+        houseWallet: houseWallet,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("Set pause state transaction signature", tx);
+
+    const account = await program.account.houseWallet.fetch(houseWallet);
+    expect(account.paused).to.be.true;
   });
 });